@@ -0,0 +1,263 @@
+//! MD5 hash chains of `salt + counter`, optionally stretched by re-hashing the hex digest.
+//!
+//! The digests can be computed on several threads and stored in either of two [`Format`]s:
+//! hex lines, one digest per line, or a compact binary file whose [`Header`] records the salt
+//! and the stretch factor, so that it can be resumed and verified later.
+
+use std::{
+    io::{self, BufRead, Read, Write},
+    thread,
+};
+
+use md5::{Digest as _, Md5};
+
+pub type Digest = [u8; 16];
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// A digest as 32 lowercase hex digits.
+pub fn to_hex(digest: &Digest) -> [u8; 32] {
+    let mut out = [0; 32];
+    for (i, byte) in digest.iter().enumerate() {
+        out[2 * i] = HEX[(byte >> 4) as usize];
+        out[2 * i + 1] = HEX[(byte & 0xf) as usize];
+    }
+    out
+}
+
+/// Parses 32 hex digits into a digest.
+pub fn from_hex(s: &str) -> Option<Digest> {
+    if s.len() != 32 {
+        return None;
+    }
+    let mut out = [0; 16];
+    for (byte, pair) in out.iter_mut().zip(s.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(out)
+}
+
+/// Hashes `salt` followed by `counter` in decimal, then hashes the hex form of the digest
+/// `stretch` more times.
+pub fn hash(salt: &[u8], counter: u64, stretch: u32) -> Digest {
+    let mut hasher = Md5::new();
+    hasher.update(salt);
+    let mut digits = [0; 20];
+    let mut n = counter;
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    hasher.update(&digits[start..]);
+    let mut digest: Digest = hasher.finalize().into();
+    for _ in 0..stretch {
+        digest = Md5::digest(to_hex(&digest)).into();
+    }
+    digest
+}
+
+/// Hashes the counters in `start..end` on `threads` threads and returns the values `filter`
+/// produced, in counter order.
+pub fn mine<T: Send>(
+    salt: &[u8],
+    stretch: u32,
+    start: u64,
+    end: u64,
+    threads: usize,
+    filter: &(impl Fn(u64, &Digest) -> Option<T> + Sync),
+) -> Vec<(u64, T)> {
+    let chunk = end
+        .saturating_sub(start)
+        .div_ceil(threads.max(1) as u64)
+        .max(1);
+    thread::scope(|s| {
+        let workers: Vec<_> = (start..end)
+            .step_by(chunk as usize)
+            .map(|from| {
+                let to = (from + chunk).min(end);
+                s.spawn(move || {
+                    (from..to)
+                        .filter_map(|i| filter(i, &hash(salt, i, stretch)).map(|t| (i, t)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().expect("hashing thread panicked"))
+            .collect()
+    })
+}
+
+/// All digests for the counters in `start..end`, in order.
+pub fn digests(salt: &[u8], stretch: u32, start: u64, end: u64, threads: usize) -> Vec<Digest> {
+    mine(salt, stretch, start, end, threads, &|_, d| Some(*d))
+        .into_iter()
+        .map(|(_, d)| d)
+        .collect()
+}
+
+/// How digests are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// One digest per line as lowercase hex, as read by the Day 14 solution in the past.
+    Hex,
+    /// A [`Header`] followed by 16 raw bytes per digest.
+    Binary,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(Self::Hex),
+            "binary" | "bin" => Ok(Self::Binary),
+            _ => Err(format!("unknown format `{s}`, expected `hex` or `binary`")),
+        }
+    }
+}
+
+const MAGIC: &[u8; 4] = b"MD5S";
+
+/// The start of a binary file: the magic bytes `MD5S`, the stretch factor as a little endian
+/// `u32`, the length of the salt as a little endian `u16` and the salt itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub salt: Vec<u8>,
+    pub stretch: u32,
+}
+
+impl Header {
+    pub fn size(&self) -> usize {
+        MAGIC.len() + 4 + 2 + self.salt.len()
+    }
+
+    pub fn write(&self, mut out: impl Write) -> io::Result<()> {
+        let len = u16::try_from(self.salt.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "the salt is too long"))?;
+        out.write_all(MAGIC)?;
+        out.write_all(&self.stretch.to_le_bytes())?;
+        out.write_all(&len.to_le_bytes())?;
+        out.write_all(&self.salt)
+    }
+
+    pub fn read(mut input: impl Read) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let mut magic = [0; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a binary md5s file"));
+        }
+        let mut stretch = [0; 4];
+        input.read_exact(&mut stretch)?;
+        let mut len = [0; 2];
+        input.read_exact(&mut len)?;
+        let mut salt = vec![0; u16::from_le_bytes(len) as usize];
+        input.read_exact(&mut salt)?;
+        Ok(Self {
+            salt,
+            stretch: u32::from_le_bytes(stretch),
+        })
+    }
+}
+
+/// Writes digests in the given format. The header of binary files is written separately.
+pub fn write_digests(mut out: impl Write, digests: &[Digest], format: Format) -> io::Result<()> {
+    for digest in digests {
+        match format {
+            Format::Hex => {
+                out.write_all(&to_hex(digest))?;
+                out.write_all(b"\n")?;
+            }
+            Format::Binary => out.write_all(digest)?,
+        }
+    }
+    Ok(())
+}
+
+/// Reads all digests of a file, together with the header if it is a binary one.
+pub fn read_digests(
+    mut input: impl BufRead,
+    format: Format,
+) -> io::Result<(Option<Header>, Vec<Digest>)> {
+    match format {
+        Format::Hex => {
+            let mut digests = vec![];
+            for (i, line) in input.lines().enumerate() {
+                let line = line?;
+                digests.push(from_hex(line.trim()).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {} is not a hex digest", i + 1),
+                    )
+                })?);
+            }
+            Ok((None, digests))
+        }
+        Format::Binary => {
+            let header = Header::read(&mut input)?;
+            let mut bytes = vec![];
+            input.read_to_end(&mut bytes)?;
+            // a trailing partial digest is left over from an interrupted run.
+            let digests = bytes
+                .chunks_exact(16)
+                .map(|c| c.try_into().expect("chunks have 16 bytes"))
+                .collect();
+            Ok((Some(header), digests))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_salt_and_counter() {
+        assert_eq!(
+            &to_hex(&hash(b"abc", 18, 0)),
+            b"0034e0923cc38887a57bd7b1d4f953df"
+        );
+        assert_eq!(
+            &to_hex(&hash(b"abc", 0, 2016)),
+            b"a107ff634856bb300138cac6568c0f24"
+        );
+        let d = hash(b"abc", 3231929, 0);
+        assert_eq!(from_hex(std::str::from_utf8(&to_hex(&d)).unwrap()), Some(d));
+    }
+
+    #[test]
+    fn mines_in_order() {
+        let serial: Vec<_> = (5..500).map(|i| hash(b"abc", i, 1)).collect();
+        assert_eq!(digests(b"abc", 1, 5, 500, 3), serial);
+    }
+
+    #[test]
+    fn roundtrips_formats() {
+        let digests = digests(b"xyz", 0, 0, 10, 1);
+        for format in [Format::Hex, Format::Binary] {
+            let mut bytes = vec![];
+            let header = Header {
+                salt: b"xyz".to_vec(),
+                stretch: 0,
+            };
+            if format == Format::Binary {
+                header.write(&mut bytes).unwrap();
+            }
+            write_digests(&mut bytes, &digests, format).unwrap();
+            if format == Format::Binary {
+                assert_eq!(bytes.len(), header.size() + 16 * 10);
+                bytes.push(0xff);
+            }
+            let (read_header, read) = read_digests(&bytes[..], format).unwrap();
+            assert_eq!(read, digests);
+            assert_eq!(read_header, (format == Format::Binary).then_some(header));
+        }
+    }
+}
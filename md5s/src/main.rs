@@ -1,31 +1,218 @@
-use std::{fs::File, io::Write};
-
-fn main() -> std::io::Result<()> {
-    let mut input = std::env::args().nth(1).unwrap();
-    let output = std::env::args().nth(2).unwrap();
-    let v2 = std::env::args()
-        .nth(3)
-        .map(|n| n.parse::<bool>().unwrap_or(false))
-        .unwrap_or(false);
-    let input_len = input.len();
-    let mut output = File::create(output)?;
-    for i in 0..100000 {
-        input.truncate(input_len);
-        input.push_str(&i.to_string());
-        if v2 {
-            let mut ans = md5::compute(&input);
-            for _ in 0..2016 {
-                let input = format!("{:x}", ans);
-                ans = md5::compute(input);
-            }
-            output.write_all(format!("{:x}\n", ans).as_bytes())?;
-            if i % 100 == 0 {
-                println!("{i}");
+//! Precomputes MD5 hash chains of `salt + counter`.
+//!
+//! ```text
+//! md5s --salt abc --output hashes.txt --count 100000 --stretch 2016
+//! md5s --salt abc --output hashes.bin --format binary --resume
+//! md5s --salt abc --output hashes.bin --format binary --verify
+//! ```
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Write},
+    process, thread,
+    time::Instant,
+};
+
+use md5s::{digests, read_digests, write_digests, Format, Header};
+
+const USAGE: &str = "\
+Usage: md5s --salt <salt> --output <file> [options]
+
+Options:
+  --count <n>        number of digests to compute [default: 100000]
+  --stretch <n>      times the hex digest is hashed again [default: 0]
+  --threads <n>      worker threads [default: all cores]
+  --format <format>  `hex` lines or compact `binary` [default: hex]
+  --resume           continue an interrupted run instead of starting over
+  --verify           recompute the digests in an existing file and compare
+  --quiet            do not report progress";
+
+/// The number of digests each thread computes between progress reports.
+const CHUNK: u64 = 1 << 12;
+
+struct Options {
+    salt: String,
+    output: String,
+    count: Option<u64>,
+    stretch: u32,
+    threads: usize,
+    format: Format,
+    resume: bool,
+    verify: bool,
+    quiet: bool,
+}
+
+fn parse_args() -> Result<Options, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let options = Options {
+        resume: args.contains("--resume"),
+        verify: args.contains("--verify"),
+        quiet: args.contains("--quiet"),
+        salt: args.value_from_str("--salt")?,
+        output: args.value_from_str("--output")?,
+        count: args.opt_value_from_str("--count")?,
+        stretch: args.opt_value_from_str("--stretch")?.unwrap_or(0),
+        threads: args
+            .opt_value_from_str("--threads")?
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+            .max(1),
+        format: args.opt_value_from_str("--format")?.unwrap_or(Format::Hex),
+    };
+    let remaining = args.finish();
+    if !remaining.is_empty() {
+        return Err(pico_args::Error::ArgumentParsingFailed {
+            cause: format!("unexpected arguments: {remaining:?}"),
+        });
+    }
+    Ok(options)
+}
+
+/// Reports how far a run got on stderr.
+struct Progress {
+    quiet: bool,
+    started: Instant,
+    total: u64,
+}
+
+impl Progress {
+    fn report(&self, done: u64, skipped: u64) {
+        if self.quiet {
+            return;
+        }
+        let rate = (done - skipped) as f64 / self.started.elapsed().as_secs_f64().max(1e-9);
+        eprint!(
+            "\r{done}/{} ({:.1}%, {rate:.0} hashes/s)",
+            self.total,
+            100.0 * done as f64 / self.total.max(1) as f64
+        );
+        if done == self.total {
+            eprintln!();
+        }
+    }
+}
+
+impl Options {
+    fn header(&self) -> Header {
+        Header {
+            salt: self.salt.as_bytes().to_vec(),
+            stretch: self.stretch,
+        }
+    }
+
+    fn progress(&self, total: u64) -> Progress {
+        Progress {
+            quiet: self.quiet,
+            started: Instant::now(),
+            total,
+        }
+    }
+
+    /// The number of complete digests in the output file, which is cut to the last of them.
+    fn existing(&self) -> io::Result<u64> {
+        let Ok(metadata) = fs::metadata(&self.output) else {
+            return Ok(0);
+        };
+        let (offset, size) = match self.format {
+            Format::Hex => (0, 33),
+            Format::Binary => {
+                let header = Header::read(BufReader::new(File::open(&self.output)?))?;
+                if header != self.header() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "the file was written with a different salt or stretch factor",
+                    ));
+                }
+                (header.size() as u64, 16)
             }
+        };
+        let count = metadata.len().saturating_sub(offset) / size;
+        OpenOptions::new()
+            .write(true)
+            .open(&self.output)?
+            .set_len(offset + count * size)?;
+        Ok(count)
+    }
+
+    fn generate(&self) -> io::Result<()> {
+        let count = self.count.unwrap_or(100000);
+        let start = if self.resume { self.existing()? } else { 0 };
+        let file = if start > 0 {
+            OpenOptions::new().append(true).open(&self.output)?
         } else {
-            output.write_all(format!("{:x}\n", md5::compute(&input)).as_bytes())?;
+            File::create(&self.output)?
+        };
+        let mut out = BufWriter::new(file);
+        if start == 0 && self.format == Format::Binary {
+            self.header().write(&mut out)?;
+        }
+
+        let progress = self.progress(count);
+        let step = CHUNK * self.threads as u64;
+        let mut done = start.min(count);
+        while done < count {
+            let end = (done + step).min(count);
+            let chunk = digests(self.salt.as_bytes(), self.stretch, done, end, self.threads);
+            write_digests(&mut out, &chunk, self.format)?;
+            // flushing after every chunk makes interrupted runs resumable.
+            out.flush()?;
+            done = end;
+            progress.report(done, start);
+        }
+        Ok(())
+    }
+
+    /// Returns whether all digests in the file are correct.
+    fn verify(&self) -> io::Result<bool> {
+        let input = BufReader::new(File::open(&self.output)?);
+        let (header, stored) = read_digests(input, self.format)?;
+        if header.is_some_and(|h| h != self.header()) {
+            eprintln!("The file was written with a different salt or stretch factor");
+            return Ok(false);
         }
+        let count = self.count.unwrap_or(stored.len() as u64);
+        if (stored.len() as u64) < count {
+            eprintln!("The file holds {} of {count} digests", stored.len());
+            return Ok(false);
+        }
+
+        let progress = self.progress(count);
+        let step = CHUNK * self.threads as u64;
+        for start in (0..count).step_by(step as usize) {
+            let end = (start + step).min(count);
+            let expected = digests(self.salt.as_bytes(), self.stretch, start, end, self.threads);
+            let actual = &stored[start as usize..end as usize];
+            if let Some(i) = (0..expected.len()).find(|&i| expected[i] != actual[i]) {
+                eprintln!("Digest {} is wrong", start + i as u64);
+                return Ok(false);
+            }
+            progress.report(end, 0);
+        }
+        Ok(true)
+    }
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error: {e}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    let result = if options.verify {
+        options.verify().map(|ok| {
+            if ok {
+                println!("All digests in {} are correct", options.output);
+            } else {
+                process::exit(1);
+            }
+        })
+    } else {
+        options.generate()
+    };
+    if let Err(e) = result {
+        eprintln!("{}: {e}", options.output);
+        process::exit(1);
     }
-    output.flush()?;
-    Ok(())
 }
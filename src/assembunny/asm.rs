@@ -0,0 +1,243 @@
+//! An assembler for assembunny with labels, comments and named constants, and the matching
+//! disassembler.
+//!
+//! ```text
+//! # everything after a `#` is a comment.
+//! const COUNT = 41
+//!
+//!         cpy COUNT a
+//! loop:   inc a
+//!         dec b
+//!         jnz b loop      # labels become relative offsets.
+//! ```
+//!
+//! Labels may be used wherever an instruction takes an offset, i.e. as the second operand of
+//! `jnz` and the operand of `tgl`. Constants may be used for any immediate value. Plain
+//! assembunny programs are valid input as well.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::{Instruction, Register, Value};
+use crate::error::ParseError;
+
+/// A source line after comments, labels and constants were split off.
+struct Line<'a> {
+    number: usize,
+    text: &'a str,
+    op: &'a str,
+    args: Vec<&'a str>,
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && s.parse::<Register>().is_err()
+}
+
+/// Lowers an assembly program to plain assembunny, one instruction per line.
+pub fn assemble(source: &str) -> Result<String, ParseError> {
+    Ok(assemble_program(source)?
+        .iter()
+        .map(|i| format!("{i}\n"))
+        .collect())
+}
+
+/// Assembles a program straight to instructions.
+pub fn assemble_program(source: &str) -> Result<Vec<Instruction>, ParseError> {
+    let mut lines = vec![];
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut constants: HashMap<&str, i64> = HashMap::new();
+
+    for (i, text) in source.lines().enumerate() {
+        let number = i + 1;
+        let error = |message: String| ParseError::new(message).at_line(number, text);
+        let mut line = text.split('#').next().unwrap_or_default().trim();
+
+        if let Some(definition) = line.strip_prefix("const ") {
+            let (name, value) = definition
+                .split_once('=')
+                .ok_or_else(|| error("expected `const <name> = <value>`".into()))?;
+            let name = name.trim();
+            if !is_identifier(name) {
+                return Err(error(format!("`{name}` is not a valid constant name")));
+            }
+            let value = value.trim();
+            let value = match constants.get(value) {
+                Some(&v) => v,
+                None => value
+                    .parse()
+                    .map_err(|_| error(format!("`{value}` is not an integer")))?,
+            };
+            if labels.contains_key(name) || constants.insert(name, value).is_some() {
+                return Err(error(format!("`{name}` is defined twice")));
+            }
+            continue;
+        }
+
+        while let Some((label, rest)) = line.split_once(':') {
+            let label = label.trim();
+            if !is_identifier(label) {
+                return Err(error(format!("`{label}` is not a valid label")));
+            }
+            if constants.contains_key(label) || labels.insert(label, lines.len()).is_some() {
+                return Err(error(format!("`{label}` is defined twice")));
+            }
+            line = rest.trim();
+        }
+
+        let mut parts = line.split_whitespace();
+        if let Some(op) = parts.next() {
+            lines.push(Line {
+                number,
+                text,
+                op,
+                args: parts.collect(),
+            });
+        }
+    }
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(pc, line)| {
+            let args = line
+                .args
+                .iter()
+                .enumerate()
+                .map(|(i, &arg)| {
+                    let is_offset = matches!((line.op, i), ("jnz", 1) | ("tgl", 0));
+                    if let Some(value) = constants.get(arg) {
+                        Ok(value.to_string())
+                    } else if let Some(&target) = labels.get(arg) {
+                        if is_offset {
+                            Ok((target as i64 - pc as i64).to_string())
+                        } else {
+                            Err(format!("label `{arg}` can only be used as an offset"))
+                        }
+                    } else if is_identifier(arg) {
+                        Err(format!("`{arg}` is not defined"))
+                    } else {
+                        Ok(arg.to_string())
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>();
+
+            args.map_err(ParseError::new)
+                .and_then(|args| format!("{} {}", line.op, args.join(" ")).parse())
+                .map_err(|e| e.at_line(line.number, line.text))
+        })
+        .collect()
+}
+
+/// Turns a program back into assembly, replacing constant jump offsets with labels.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let target = |pc: usize, offset: i64| {
+        pc.checked_add_signed(offset as isize)
+            .filter(|&t| t <= program.len())
+    };
+
+    let targets: BTreeSet<usize> = program
+        .iter()
+        .enumerate()
+        .filter_map(|(pc, i)| match *i {
+            Instruction::Jnz(_, Value::Imm(offset)) => target(pc, offset),
+            _ => None,
+        })
+        .collect();
+    let label = |t: usize| {
+        if t == program.len() {
+            "end".to_string()
+        } else {
+            format!("l{t}")
+        }
+    };
+
+    let mut out = String::new();
+    for (pc, instruction) in program.iter().enumerate() {
+        if targets.contains(&pc) {
+            out.push_str(&format!("{}:\n", label(pc)));
+        }
+        let line = match *instruction {
+            Instruction::Jnz(x, Value::Imm(offset)) => match target(pc, offset) {
+                Some(t) => format!("jnz {x} {}", label(t)),
+                None => instruction.to_string(),
+            },
+            _ => instruction.to_string(),
+        };
+        out.push_str(&format!("    {line}\n"));
+    }
+    if targets.contains(&program.len()) {
+        out.push_str("end:\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assemble, assemble_program, disassemble};
+    use crate::assembunny::parse;
+
+    #[test]
+    fn lowers_labels_and_constants() {
+        let source = "\
+# adds b to a
+const START = 41
+const COPY = START
+
+        cpy COPY a
+loop:   inc a       # labels are relative
+        dec b
+        jnz b loop
+        jnz 1 end
+        tgl done
+done:
+end:
+";
+        assert_eq!(
+            assemble(source).unwrap(),
+            "cpy 41 a\ninc a\ndec b\njnz b -2\njnz 1 2\ntgl 1\n"
+        );
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        let err = assemble_program("loop: inc a\nloop: dec a\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: `loop` is defined twice");
+
+        let err = assemble_program("inc a\njnz a nowhere\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: `nowhere` is not defined");
+
+        let err = assemble_program("x: cpy x a\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1: label `x` can only be used as an offset"
+        );
+
+        let err = assemble_program("const N = 4\n\ninc N\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 5: `4` is not a valid register"
+        );
+    }
+
+    #[test]
+    fn accepts_plain_programs() {
+        let plain = "cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a\n";
+        assert_eq!(assemble_program(plain).unwrap(), parse(plain).unwrap());
+    }
+
+    #[test]
+    fn roundtrips_disassembly() {
+        let program =
+            parse("cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a\njnz 1 -5\njnz a 9\njnz b c\n")
+                .unwrap();
+        let listing = disassemble(&program);
+        assert!(listing.contains("l1:\n    inc a\n"), "{listing}");
+        assert!(listing.contains("    jnz a l6\n"), "{listing}");
+        assert!(listing.contains("    jnz a 9\n"), "{listing}");
+        assert_eq!(assemble_program(&listing).unwrap(), program);
+    }
+}
@@ -0,0 +1,746 @@
+//! Control-flow analysis and decompilation of assembunny programs.
+//!
+//! [`Cfg::new`] splits a program into basic blocks, treating the loops found by
+//! [`optimize::analyze`] as single statements, and finds the loops between the blocks.
+//! The graph can be rendered as DOT with [`Cfg::to_dot`] or as pseudo-code with [`Cfg::decompile`].
+//!
+//! Both describe the program as written. Code rewritten by `tgl` at runtime is not accounted for.
+
+use std::{collections::BTreeSet, fmt::Display, fmt::Write};
+
+use super::{
+    optimize::{self, Fused},
+    Instruction, Register, Value,
+};
+
+/// An instruction, or a loop that is executed as a whole.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Statement {
+    Instruction(Instruction),
+    Fused(Fused),
+}
+
+impl Statement {
+    fn size(&self) -> usize {
+        match self {
+            Statement::Instruction(_) => 1,
+            Statement::Fused(f) => f.size(),
+        }
+    }
+
+    /// Registers the statement writes to.
+    fn writes(&self) -> Vec<Register> {
+        match *self {
+            Statement::Instruction(
+                Instruction::Cpy(_, Value::Reg(r))
+                | Instruction::Inc(Value::Reg(r))
+                | Instruction::Dec(Value::Reg(r)),
+            ) => vec![r],
+            Statement::Instruction(_) => vec![],
+            Statement::Fused(Fused::Add {
+                target, counter, ..
+            }) => vec![target, counter],
+            Statement::Fused(Fused::Mul {
+                target,
+                inner,
+                outer,
+                ..
+            }) => vec![target, inner, outer],
+        }
+    }
+}
+
+/// Where control goes when leaving a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Block(usize),
+    /// The program counter leaves the program.
+    Halt,
+    /// A `jnz` with an offset read from a register.
+    Dynamic,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    /// First instruction of the block.
+    pub start: usize,
+    /// Instruction after the block.
+    pub end: usize,
+    pub statements: Vec<(usize, Statement)>,
+    /// Target of the `jnz` ending the block, if it can jump.
+    pub jump: Option<Target>,
+    /// Where control falls through to, unless the block always jumps.
+    pub next: Option<Target>,
+}
+
+impl Block {
+    fn successors(&self) -> impl Iterator<Item = usize> + '_ {
+        self.jump
+            .iter()
+            .chain(self.next.iter())
+            .filter_map(|t| match t {
+                Target::Block(b) => Some(*b),
+                _ => None,
+            })
+    }
+
+    /// The condition of the `jnz` ending the block, if it is conditional.
+    fn condition(&self) -> Option<Value> {
+        match self.statements.last() {
+            Some((_, Statement::Instruction(Instruction::Jnz(x, _))))
+                if self.jump.is_some() && self.next.is_some() =>
+            {
+                Some(*x)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A natural loop: the blocks that can reach a back edge to `header` without passing through it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Loop {
+    pub header: usize,
+    /// Blocks with a back edge to the header.
+    pub latches: Vec<usize>,
+    pub blocks: BTreeSet<usize>,
+    /// Registers that are only ever incremented or decremented in the loop, with their net change
+    /// when every block of the loop runs once.
+    pub induction: Vec<(Register, i64)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cfg {
+    pub blocks: Vec<Block>,
+    pub loops: Vec<Loop>,
+    /// Whether the program contains `tgl`, in which case the graph may not match what runs.
+    pub self_modifying: bool,
+}
+
+/// Target of a `jnz` with a constant offset.
+fn jump_target(pc: usize, instruction: &Instruction) -> Option<usize> {
+    match *instruction {
+        Instruction::Jnz(_, Value::Imm(offset)) => pc.checked_add_signed(offset as isize),
+        _ => None,
+    }
+}
+
+impl Cfg {
+    pub fn new(program: &[Instruction]) -> Self {
+        let targets: Vec<(usize, usize)> = program
+            .iter()
+            .enumerate()
+            .filter_map(|(pc, i)| jump_target(pc, i).map(|t| (pc, t)))
+            .collect();
+
+        // fuse loops unless code outside of them jumps into their middle.
+        let fused = optimize::analyze(program);
+        let mut statements = vec![];
+        let mut pc = 0;
+        while pc < program.len() {
+            let statement = match fused[pc] {
+                Some(f)
+                    if !targets.iter().any(|&(from, to)| {
+                        let inside = pc..pc + f.size();
+                        !inside.contains(&from) && to > pc && inside.contains(&to)
+                    }) =>
+                {
+                    Statement::Fused(f)
+                }
+                _ => Statement::Instruction(program[pc]),
+            };
+            statements.push((pc, statement));
+            pc += statement.size();
+        }
+
+        let mut leaders = BTreeSet::from([0]);
+        for (pc, statement) in &statements {
+            if let Statement::Instruction(i @ Instruction::Jnz(..)) = statement {
+                leaders.insert(pc + 1);
+                leaders.extend(jump_target(*pc, i));
+            }
+        }
+        leaders.retain(|&pc| pc < program.len());
+
+        let mut blocks: Vec<Block> = vec![];
+        for (pc, statement) in statements {
+            if leaders.contains(&pc) || blocks.is_empty() {
+                blocks.push(Block {
+                    start: pc,
+                    end: pc,
+                    statements: vec![],
+                    jump: None,
+                    next: None,
+                });
+            }
+            let block = blocks.last_mut().unwrap();
+            block.end = pc + statement.size();
+            block.statements.push((pc, statement));
+        }
+
+        let block_at = |pc: Option<usize>| match pc {
+            Some(pc) => match blocks.iter().position(|b| b.start == pc) {
+                Some(b) => Target::Block(b),
+                None => Target::Halt,
+            },
+            None => Target::Halt,
+        };
+
+        let edges: Vec<(Option<Target>, Option<Target>)> = blocks
+            .iter()
+            .map(|block| {
+                let next = block_at(Some(block.end));
+                match block.statements.last() {
+                    Some(&(pc, Statement::Instruction(i @ Instruction::Jnz(x, y)))) => {
+                        let jump = match y {
+                            Value::Imm(_) => block_at(jump_target(pc, &i)),
+                            Value::Reg(_) => Target::Dynamic,
+                        };
+                        match x {
+                            Value::Imm(0) => (None, Some(next)),
+                            Value::Imm(_) => (Some(jump), None),
+                            Value::Reg(_) => (Some(jump), Some(next)),
+                        }
+                    }
+                    _ => (None, Some(next)),
+                }
+            })
+            .collect();
+        for (block, (jump, next)) in blocks.iter_mut().zip(edges) {
+            block.jump = jump;
+            block.next = next;
+        }
+
+        let loops = find_loops(&blocks);
+        let self_modifying = program.iter().any(|i| matches!(i, Instruction::Tgl(_)));
+
+        Self {
+            blocks,
+            loops,
+            self_modifying,
+        }
+    }
+
+    /// Renders the graph in the DOT format of Graphviz.
+    pub fn to_dot(&self, program: &[Instruction]) -> String {
+        let mut dot = String::from("digraph assembunny {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        dot.push_str("    halt [shape=doublecircle];\n");
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            let mut label = String::new();
+            for (pc, instruction) in program.iter().enumerate().take(block.end).skip(block.start) {
+                let _ = write!(label, "{pc}: {instruction}\\l");
+            }
+            let style = if self.loops.iter().any(|l| l.header == i) {
+                ", style=bold"
+            } else {
+                ""
+            };
+            let _ = writeln!(dot, "    b{i} [label=\"{label}\"{style}];");
+        }
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            let condition = block.condition();
+            let edges = [
+                (block.jump, condition.map(|c| format!("{c} != 0"))),
+                (block.next, condition.map(|c| format!("{c} == 0"))),
+            ];
+            for (target, label) in edges {
+                let Some(target) = target else {
+                    continue;
+                };
+                let to = match target {
+                    Target::Block(b) => format!("b{b}"),
+                    Target::Halt => "halt".into(),
+                    Target::Dynamic => {
+                        let _ = writeln!(dot, "    dynamic{i} [label=\"?\", shape=circle];");
+                        format!("dynamic{i}")
+                    }
+                };
+                let mut attributes = vec![];
+                if let Some(label) = label {
+                    attributes.push(format!("label=\"{label}\""));
+                }
+                if self.is_back_edge(i, target) {
+                    attributes.push("style=dashed".into());
+                }
+                let attributes = if attributes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", attributes.join(", "))
+                };
+                let _ = writeln!(dot, "    b{i} -> {to}{attributes};");
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn is_back_edge(&self, from: usize, to: Target) -> bool {
+        self.loops
+            .iter()
+            .any(|l| to == Target::Block(l.header) && l.latches.contains(&from))
+    }
+
+    /// Renders the program as pseudo-code. Each block becomes a label followed by the combined
+    /// effect of its statements, so straight-line code and fused loops collapse into expressions
+    /// like `d = a + 7 * 362`.
+    pub fn decompile(&self) -> String {
+        let mut out = String::new();
+        if self.self_modifying {
+            out.push_str(
+                "// the program uses tgl, the code below is only accurate until it runs\n",
+            );
+        }
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            let _ = write!(out, "L{}:", block.start);
+            for l in self.loops.iter().filter(|l| l.header == i) {
+                let latches: Vec<String> = l
+                    .latches
+                    .iter()
+                    .map(|&b| format!("L{}", self.blocks[b].start))
+                    .collect();
+                let _ = write!(out, "  // loop back from {}", latches.join(", "));
+                if !l.induction.is_empty() {
+                    let induction: Vec<String> = l
+                        .induction
+                        .iter()
+                        .map(|(r, step)| format!("{r} {step:+}"))
+                        .collect();
+                    let _ = write!(out, ", induction: {}", induction.join(", "));
+                }
+            }
+            out.push('\n');
+
+            for line in block_body(block) {
+                let _ = writeln!(out, "    {line}");
+            }
+            for line in self.terminator(i) {
+                let _ = writeln!(out, "    {line}");
+            }
+        }
+        out
+    }
+
+    fn label(&self, target: Target) -> String {
+        match target {
+            Target::Block(b) => format!("goto L{}", self.blocks[b].start),
+            Target::Halt => "halt".into(),
+            Target::Dynamic => "goto computed".into(),
+        }
+    }
+
+    fn terminator(&self, i: usize) -> Vec<String> {
+        let block = &self.blocks[i];
+        let dynamic = match block.statements.last() {
+            Some((pc, Statement::Instruction(Instruction::Jnz(_, Value::Reg(r))))) => {
+                format!("goto {pc} + {r}")
+            }
+            _ => String::new(),
+        };
+        let jump = |target: Target| match target {
+            Target::Dynamic => dynamic.clone(),
+            target => self.label(target),
+        };
+
+        let mut lines = vec![];
+        match (block.jump, block.condition()) {
+            (Some(target), Some(condition)) => {
+                lines.push(format!("if {condition} != 0 {{ {} }}", jump(target)));
+            }
+            (Some(target), None) => lines.push(jump(target)),
+            (None, _) => {}
+        }
+        match block.next {
+            // falling through to the next block needs no jump.
+            Some(Target::Block(b)) if b == i + 1 => {}
+            Some(target) => lines.push(jump(target)),
+            None => {}
+        }
+        lines
+    }
+}
+
+fn find_loops(blocks: &[Block]) -> Vec<Loop> {
+    let n = blocks.len();
+    let mut predecessors = vec![vec![]; n];
+    for (i, block) in blocks.iter().enumerate() {
+        for s in block.successors() {
+            predecessors[s].push(i);
+        }
+    }
+
+    // blocks only reachable through dynamic jumps are treated as additional entries.
+    let mut roots = vec![];
+    let mut reachable = vec![false; n];
+    while let Some(root) = reachable.iter().position(|r| !r) {
+        roots.push(root);
+        let mut stack = vec![root];
+        while let Some(b) = stack.pop() {
+            if !reachable[b] {
+                reachable[b] = true;
+                stack.extend(blocks[b].successors());
+            }
+        }
+    }
+
+    // iterative dominator sets.
+    let mut dominators = vec![(0..n).collect::<BTreeSet<usize>>(); n];
+    for &root in &roots {
+        dominators[root] = BTreeSet::from([root]);
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for b in (0..n).filter(|b| !roots.contains(b)) {
+            let mut new = predecessors[b]
+                .iter()
+                .map(|&p| dominators[p].clone())
+                .reduce(|a, b| a.intersection(&b).copied().collect())
+                .unwrap_or_default();
+            new.insert(b);
+            if new != dominators[b] {
+                dominators[b] = new;
+                changed = true;
+            }
+        }
+    }
+
+    let mut loops: Vec<Loop> = vec![];
+    for (latch, block) in blocks.iter().enumerate() {
+        for header in block.successors() {
+            if !dominators[latch].contains(&header) {
+                continue;
+            }
+
+            let mut body = BTreeSet::from([header, latch]);
+            let mut stack = vec![latch];
+            while let Some(b) = stack.pop() {
+                if b == header {
+                    continue;
+                }
+                for &p in &predecessors[b] {
+                    if body.insert(p) {
+                        stack.push(p);
+                    }
+                }
+            }
+
+            match loops.iter_mut().find(|l| l.header == header) {
+                Some(l) => {
+                    l.latches.push(latch);
+                    l.blocks.extend(body);
+                }
+                None => loops.push(Loop {
+                    header,
+                    latches: vec![latch],
+                    blocks: body,
+                    induction: vec![],
+                }),
+            }
+        }
+    }
+
+    for l in &mut loops {
+        l.induction = induction_registers(blocks, &l.blocks);
+    }
+    loops.sort_by_key(|l| l.header);
+    loops
+}
+
+fn induction_registers(blocks: &[Block], body: &BTreeSet<usize>) -> Vec<(Register, i64)> {
+    let mut steps = [Some(0_i64); 4];
+    for &b in body {
+        for (_, statement) in &blocks[b].statements {
+            match statement {
+                Statement::Instruction(Instruction::Inc(Value::Reg(r))) => {
+                    steps[*r as usize] = steps[*r as usize].map(|s| s + 1);
+                }
+                Statement::Instruction(Instruction::Dec(Value::Reg(r))) => {
+                    steps[*r as usize] = steps[*r as usize].map(|s| s - 1);
+                }
+                s => {
+                    for r in s.writes() {
+                        steps[r as usize] = None;
+                    }
+                }
+            }
+        }
+    }
+
+    Register::ALL
+        .iter()
+        .filter_map(|&r| match steps[r as usize] {
+            Some(step) if step != 0 => Some((r, step)),
+            _ => None,
+        })
+        .collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A symbolic value of a register in terms of the registers at the start of a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Reg(Register),
+    /// The value a register had before an assignment in the same block overwrote it.
+    Old(Register),
+    Const(i64),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn from_value(value: Value) -> Self {
+        match value {
+            Value::Reg(r) => Expr::Reg(r),
+            Value::Imm(i) => Expr::Const(i),
+        }
+    }
+
+    fn add(self, rhs: Expr) -> Self {
+        match (self, rhs) {
+            (x, Expr::Const(0)) | (Expr::Const(0), x) => x,
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const(a.wrapping_add(b)),
+            // fold `x + 1 + 1` into `x + 2`.
+            (Expr::Add(x, y), Expr::Const(b)) => match *y {
+                Expr::Const(a) => x.add(Expr::Const(a.wrapping_add(b))),
+                y => Expr::Add(
+                    Box::new(Expr::Add(x, Box::new(y))),
+                    Box::new(Expr::Const(b)),
+                ),
+            },
+            (x, y) => Expr::Add(Box::new(x), Box::new(y)),
+        }
+    }
+
+    fn sub(self, rhs: Expr) -> Self {
+        match rhs {
+            Expr::Const(c) => self.add(Expr::Const(c.wrapping_neg())),
+            rhs => Expr::Sub(Box::new(self), Box::new(rhs)),
+        }
+    }
+
+    fn mul(self, rhs: Expr) -> Self {
+        match (self, rhs) {
+            (Expr::Const(0), _) | (_, Expr::Const(0)) => Expr::Const(0),
+            (x, Expr::Const(1)) | (Expr::Const(1), x) => x,
+            (x, y) => Expr::Mul(Box::new(x), Box::new(y)),
+        }
+    }
+
+    fn reads(&self, register: Register) -> bool {
+        match self {
+            Expr::Reg(r) => *r == register,
+            Expr::Old(_) | Expr::Const(_) => false,
+            Expr::Add(x, y) | Expr::Sub(x, y) | Expr::Mul(x, y) => {
+                x.reads(register) || y.reads(register)
+            }
+        }
+    }
+
+    fn replace(&self, register: Register, with: &Expr) -> Expr {
+        match self {
+            Expr::Reg(r) if *r == register => with.clone(),
+            Expr::Add(x, y) => Expr::Add(
+                Box::new(x.replace(register, with)),
+                Box::new(y.replace(register, with)),
+            ),
+            Expr::Sub(x, y) => Expr::Sub(
+                Box::new(x.replace(register, with)),
+                Box::new(y.replace(register, with)),
+            ),
+            Expr::Mul(x, y) => Expr::Mul(
+                Box::new(x.replace(register, with)),
+                Box::new(y.replace(register, with)),
+            ),
+            e => e.clone(),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Add(..) | Expr::Sub(..) => 1,
+            Expr::Const(c) if *c < 0 => 1,
+            Expr::Mul(..) => 2,
+            _ => 3,
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let wrap = |e: &Expr, min: u8| {
+            if e.precedence() < min {
+                format!("({e})")
+            } else {
+                e.to_string()
+            }
+        };
+        match self {
+            Expr::Reg(r) => write!(f, "{r}"),
+            Expr::Old(r) => write!(f, "old_{r}"),
+            Expr::Const(c) => write!(f, "{c}"),
+            Expr::Add(x, y) => match **y {
+                Expr::Const(c) if c < 0 => write!(f, "{x} - {}", c.unsigned_abs()),
+                _ => write!(f, "{x} + {y}"),
+            },
+            Expr::Sub(x, y) => write!(f, "{x} - {}", wrap(y, 2)),
+            Expr::Mul(x, y) => write!(f, "{} * {}", wrap(x, 2), wrap(y, 3)),
+        }
+    }
+}
+
+/// Symbolically executes a block and returns its pseudo-code, without the final jump.
+fn block_body(block: &Block) -> Vec<String> {
+    let mut state = Register::ALL.map(Expr::Reg);
+    let mut lines = vec![];
+
+    for &(pc, statement) in &block.statements {
+        match statement {
+            Statement::Instruction(instruction) => match instruction {
+                Instruction::Cpy(x, Value::Reg(r)) => state[r as usize] = value(&state, x),
+                Instruction::Inc(Value::Reg(r)) => {
+                    state[r as usize] = state[r as usize].clone().add(Expr::Const(1));
+                }
+                Instruction::Dec(Value::Reg(r)) => {
+                    state[r as usize] = state[r as usize].clone().sub(Expr::Const(1));
+                }
+                Instruction::Tgl(x) => lines.push(format!("tgl({pc} + {})", value(&state, x))),
+                Instruction::Out(x) => lines.push(format!("out({})", value(&state, x))),
+                // invalid instructions do nothing, jumps end the block.
+                _ => {}
+            },
+            Statement::Fused(Fused::Add {
+                target,
+                counter,
+                subtract,
+            }) => {
+                let (t, c) = (
+                    state[target as usize].clone(),
+                    state[counter as usize].clone(),
+                );
+                state[target as usize] = if subtract { t.sub(c) } else { t.add(c) };
+                state[counter as usize] = Expr::Const(0);
+            }
+            Statement::Fused(Fused::Mul {
+                target,
+                factor,
+                inner,
+                outer,
+                subtract,
+            }) => {
+                let t = state[target as usize].clone();
+                let product = state[outer as usize].clone().mul(value(&state, factor));
+                state[target as usize] = if subtract {
+                    t.sub(product)
+                } else {
+                    t.add(product)
+                };
+                state[inner as usize] = Expr::Const(0);
+                state[outer as usize] = Expr::Const(0);
+            }
+        }
+    }
+
+    // the assignments happen at once, order them so no register is overwritten before it is read.
+    let mut pending: Vec<(Register, Expr)> = Register::ALL
+        .into_iter()
+        .filter(|&r| state[r as usize] != Expr::Reg(r))
+        .map(|r| (r, state[r as usize].clone()))
+        .collect();
+
+    while !pending.is_empty() {
+        let free = pending
+            .iter()
+            .position(|(r, _)| pending.iter().all(|(other, e)| other == r || !e.reads(*r)));
+        let index = match free {
+            Some(index) => index,
+            None => {
+                // a cycle like `a = b; b = a`, save one of the registers first.
+                let (r, _) = pending[0];
+                lines.push(format!("old_{r} = {r}"));
+                for (_, e) in &mut pending {
+                    *e = e.replace(r, &Expr::Old(r));
+                }
+                0
+            }
+        };
+        let (r, e) = pending.remove(index);
+        lines.push(format!("{r} = {e}"));
+    }
+
+    lines
+}
+
+fn value(state: &[Expr; 4], value: Value) -> Expr {
+    match value {
+        Value::Reg(r) => state[r as usize].clone(),
+        v => Expr::from_value(v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cfg, Target};
+    use crate::assembunny::{parse, Register};
+
+    const CLOCK: &str = "cpy a d\ncpy 7 c\ncpy 362 b\ninc d\ndec b\njnz b -2\ndec c\njnz c -5\ncpy d a\njnz 0 0\ncpy a b\ncpy 0 a\ncpy 2 c\njnz b 2\njnz 1 6\ndec b\ndec c\njnz c -4\ninc a\njnz 1 -7\ncpy 2 b\njnz c 2\njnz 1 4\ndec b\ndec c\njnz 1 -4\njnz 0 0\nout b\njnz a -19\njnz 1 -21\n";
+
+    #[test]
+    fn builds_blocks_and_loops() {
+        let program = parse(CLOCK).unwrap();
+        let cfg = Cfg::new(&program);
+
+        assert_eq!(cfg.blocks[0].start, 0);
+        assert_eq!(cfg.blocks[0].end, 8);
+        assert_eq!(cfg.blocks[0].next, Some(Target::Block(1)));
+        assert!(!cfg.self_modifying);
+
+        // the halving loop that counts `c` down from 2 and `b` down to 0.
+        let halving = cfg
+            .loops
+            .iter()
+            .find(|l| cfg.blocks[l.header].start == 13)
+            .unwrap();
+        assert!(halving.induction.contains(&(Register::B, -1)));
+        // the outer loop around `out`, and the one restarting the signal.
+        assert!(cfg.loops.len() >= 3);
+    }
+
+    #[test]
+    fn decompiles_fused_loops() {
+        let program = parse(CLOCK).unwrap();
+        let code = Cfg::new(&program).decompile();
+        assert!(code.contains("    d = a + 7 * 362\n"), "{code}");
+        assert!(code.contains("    out(b)\n"), "{code}");
+        assert!(code.contains("    if b != 0 { goto L15 }\n"), "{code}");
+    }
+
+    #[test]
+    fn orders_parallel_assignments() {
+        let program = parse("cpy a c\ncpy b a\ncpy c b\ninc c\n").unwrap();
+        let code = Cfg::new(&program).decompile();
+        assert_eq!(
+            code,
+            "L0:\n    c = a + 1\n    old_a = a\n    a = b\n    b = old_a\n    halt\n"
+        );
+    }
+
+    #[test]
+    fn renders_dot() {
+        let program = parse("cpy 3 a\ndec a\njnz a -1\njnz b c\n").unwrap();
+        let cfg = Cfg::new(&program);
+        let dot = cfg.to_dot(&program);
+        assert!(
+            dot.contains("b1 -> b1 [label=\"a != 0\", style=dashed];"),
+            "{dot}"
+        );
+        assert!(dot.contains("b2 -> dynamic2"), "{dot}");
+        assert!(dot.contains("b2 -> halt"), "{dot}");
+    }
+}
@@ -0,0 +1,257 @@
+//! Compilation of assembunny programs to a flat bytecode.
+//!
+//! Operands are resolved ahead of time: immediates are converted to the register type and jumps
+//! with constant offsets point straight at their target. Because `tgl` rewrites the program, a
+//! [`Compiled`] program only runs until the first instruction gets toggled and hands over to the
+//! interpreter from there.
+
+use super::{optimize, Control, Exit, Instruction, Machine, Register, Value, Word};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operand<W> {
+    Reg(Register),
+    Imm(W),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op<W> {
+    Cpy(Operand<W>, Register),
+    Inc(Register),
+    Dec(Register),
+    /// Jumps to the target if the register is not zero.
+    JumpIf(Register, usize),
+    Jump(usize),
+    /// A `jnz` whose offset is read from a register.
+    JumpBy(Operand<W>, Register),
+    Tgl(Operand<W>),
+    Out(Operand<W>),
+    /// Invalid instructions and `jnz 0 x`.
+    Nop,
+}
+
+/// A program compiled for registers of type `W`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Compiled<W = i64> {
+    source: Vec<Instruction>,
+    ops: Vec<Op<W>>,
+    /// Whether a fused loop starts at an instruction, see [`Machine::optimize`].
+    fused: Vec<bool>,
+}
+
+impl<W: Word> Compiled<W> {
+    pub fn new(program: &[Instruction]) -> Self {
+        let operand = |v: Value| match v {
+            Value::Reg(r) => Operand::Reg(r),
+            Value::Imm(i) => Operand::Imm(W::from_i64(i)),
+        };
+
+        let ops = program
+            .iter()
+            .enumerate()
+            .map(|(pc, &instruction)| match instruction {
+                Instruction::Cpy(x, Value::Reg(r)) => Op::Cpy(operand(x), r),
+                Instruction::Inc(Value::Reg(r)) => Op::Inc(r),
+                Instruction::Dec(Value::Reg(r)) => Op::Dec(r),
+                Instruction::Jnz(x, Value::Imm(offset)) => {
+                    let target = pc.wrapping_add_signed(offset as isize);
+                    match x {
+                        Value::Reg(r) => Op::JumpIf(r, target),
+                        Value::Imm(0) => Op::Nop,
+                        Value::Imm(_) => Op::Jump(target),
+                    }
+                }
+                Instruction::Jnz(x, Value::Reg(r)) => Op::JumpBy(operand(x), r),
+                Instruction::Tgl(x) => Op::Tgl(operand(x)),
+                Instruction::Out(x) => Op::Out(operand(x)),
+                Instruction::Cpy(_, Value::Imm(_))
+                | Instruction::Inc(Value::Imm(_))
+                | Instruction::Dec(Value::Imm(_)) => Op::Nop,
+            })
+            .collect();
+
+        Self {
+            source: program.to_vec(),
+            ops,
+            fused: optimize::analyze(program)
+                .iter()
+                .map(Option::is_some)
+                .collect(),
+        }
+    }
+
+    /// Runs the machine like [`Machine::run_with`] does, including fused loops if the machine is
+    /// optimized. Falls back to the interpreter once the program no longer matches the compiled one.
+    pub fn run(
+        &self,
+        machine: &mut Machine<W>,
+        max_steps: Option<u64>,
+        mut output: impl FnMut(W) -> Control,
+    ) -> Exit {
+        if machine.program != self.source {
+            return machine.run_with(max_steps, output);
+        }
+
+        let optimized = machine.is_optimized();
+        let limit = max_steps.unwrap_or(u64::MAX);
+        let start = machine.steps;
+
+        // the state lives in locals while running and is written back to the machine on exit.
+        let mut registers = machine.registers;
+        let mut pc = machine.pc;
+        let mut steps = 0;
+
+        macro_rules! read {
+            ($operand:expr) => {
+                match $operand {
+                    Operand::Reg(r) => registers[r as usize],
+                    Operand::Imm(i) => i,
+                }
+            };
+        }
+
+        macro_rules! store {
+            () => {
+                machine.registers = registers;
+                machine.pc = pc;
+                machine.steps = start + steps;
+            };
+        }
+
+        let exit = loop {
+            if steps == limit {
+                break Exit::StepLimit;
+            }
+            let Some(&op) = self.ops.get(pc) else {
+                break Exit::Halted;
+            };
+
+            if optimized && self.fused[pc] {
+                store!();
+                if machine.step_fused(max_steps.map(|_| limit - steps)) {
+                    registers = machine.registers;
+                    pc = machine.pc;
+                    steps = machine.steps - start;
+                    if pc >= self.ops.len() {
+                        break Exit::Halted;
+                    }
+                    continue;
+                }
+            }
+
+            let mut next = pc + 1;
+            match op {
+                Op::Cpy(x, r) => registers[r as usize] = read!(x),
+                Op::Inc(r) => {
+                    registers[r as usize] = registers[r as usize].wrapping_add(W::from_i64(1));
+                }
+                Op::Dec(r) => {
+                    registers[r as usize] = registers[r as usize].wrapping_sub(W::from_i64(1));
+                }
+                Op::JumpIf(r, target) => {
+                    if !registers[r as usize].is_zero() {
+                        next = target;
+                    }
+                }
+                Op::Jump(target) => next = target,
+                Op::JumpBy(x, r) => {
+                    if !read!(x).is_zero() {
+                        next = pc.wrapping_add_signed(registers[r as usize].to_i64() as isize);
+                    }
+                }
+                Op::Tgl(x) => {
+                    let target = pc.wrapping_add_signed(read!(x).to_i64() as isize);
+                    if target < self.ops.len() {
+                        // the program is about to change, let the interpreter take over.
+                        store!();
+                        if let Some(exit) = machine.step(&mut output) {
+                            return exit;
+                        }
+                        return machine.run_with(max_steps.map(|_| limit - steps - 1), output);
+                    }
+                }
+                Op::Out(x) => {
+                    if output(read!(x)) == Control::Stop {
+                        pc = next;
+                        steps += 1;
+                        break Exit::Stopped;
+                    }
+                }
+                Op::Nop => {}
+            }
+
+            pc = next;
+            steps += 1;
+            if pc >= self.ops.len() {
+                break Exit::Halted;
+            }
+        };
+
+        store!();
+        exit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compiled;
+    use crate::assembunny::{optimize, parse, Control, Exit, Machine, Register};
+
+    #[test]
+    fn runs_programs() {
+        let program = parse("cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a\n").unwrap();
+        let mut machine = Machine::<u32>::new(program.clone());
+        let exit = Compiled::new(&program).run(&mut machine, None, |_| Control::Continue);
+        assert_eq!(exit, Exit::Halted);
+        assert_eq!(machine[Register::A], 42);
+        assert_eq!(machine.steps, 5);
+    }
+
+    #[test]
+    fn hands_over_to_interpreter_on_tgl() {
+        let program = parse("cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a\n").unwrap();
+        let compiled = Compiled::new(&program);
+
+        let mut machine = Machine::<i64>::new(program.clone());
+        assert_eq!(
+            compiled.run(&mut machine, None, |_| Control::Continue),
+            Exit::Halted
+        );
+        assert_eq!(machine[Register::A], 3);
+
+        // the program was toggled, so running it again must not use the compiled code.
+        assert_ne!(machine.program, program);
+        machine.pc = 0;
+        machine.registers = [0; 4];
+        let mut interpreted = machine.clone();
+        compiled.run(&mut machine, None, |_| Control::Continue);
+        interpreted.run();
+        assert_eq!(machine, interpreted);
+    }
+
+    #[test]
+    fn matches_interpreter_on_random_programs() {
+        for (program, registers, max_steps) in optimize::tests::random_programs(300) {
+            let compiled = Compiled::new(&program);
+            for optimize in [false, true] {
+                let run = |compile: bool| {
+                    let mut machine = Machine::with_registers(program.clone(), registers);
+                    if optimize {
+                        machine.optimize();
+                    }
+                    let mut output = vec![];
+                    let sink = |v| {
+                        output.push(v);
+                        Control::Continue
+                    };
+                    let exit = if compile {
+                        compiled.run(&mut machine, Some(max_steps), sink)
+                    } else {
+                        machine.run_with(Some(max_steps), sink)
+                    };
+                    (exit, machine, output)
+                };
+                assert_eq!(run(true), run(false), "{program:?} {registers:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::{Index, IndexMut},
+};
+
+use super::{
+    optimize::{self, Fused},
+    Instruction, Register, Value,
+};
+
+/// The type of a machine register. Arithmetic wraps at the width of the type.
+pub trait Word: Copy + Default + Eq + Hash + Debug + Display {
+    fn from_i64(value: i64) -> Self;
+    fn to_i64(self) -> i64;
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    fn wrapping_mul(self, rhs: Self) -> Self;
+
+    fn is_zero(self) -> bool {
+        self == Self::default()
+    }
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {
+        $(
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+            impl Word for $t {
+                fn from_i64(value: i64) -> Self {
+                    value as $t
+                }
+
+                fn to_i64(self) -> i64 {
+                    self as i64
+                }
+
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$t>::wrapping_add(self, rhs)
+                }
+
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    <$t>::wrapping_sub(self, rhs)
+                }
+
+                fn wrapping_mul(self, rhs: Self) -> Self {
+                    <$t>::wrapping_mul(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(i32, i64, isize, u32, u64);
+
+/// Returned by an output sink to decide whether the machine keeps running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Stop,
+}
+
+/// Why a machine stopped running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    /// The program counter left the program.
+    Halted,
+    /// The step limit was reached.
+    StepLimit,
+    /// The output sink asked to stop.
+    Stopped,
+}
+
+/// An assembunny machine with four registers of type `W`.
+///
+/// The machine owns its program, since `tgl` rewrites it while running.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Machine<W = i64> {
+    pub registers: [W; 4],
+    pub pc: usize,
+    pub program: Vec<Instruction>,
+    /// Number of instructions executed so far.
+    pub steps: u64,
+    /// Loops to fuse by starting instruction. Empty unless optimizations are enabled.
+    fused: Vec<Option<Fused>>,
+}
+
+impl<W: Word> Index<Register> for Machine<W> {
+    type Output = W;
+
+    fn index(&self, index: Register) -> &Self::Output {
+        &self.registers[index as usize]
+    }
+}
+
+impl<W: Word> IndexMut<Register> for Machine<W> {
+    fn index_mut(&mut self, index: Register) -> &mut Self::Output {
+        &mut self.registers[index as usize]
+    }
+}
+
+impl<W: Word> Machine<W> {
+    pub fn new(program: Vec<Instruction>) -> Self {
+        Self::with_registers(program, [W::default(); 4])
+    }
+
+    pub fn with_registers(program: Vec<Instruction>, registers: [W; 4]) -> Self {
+        Self {
+            registers,
+            pc: 0,
+            program,
+            steps: 0,
+            fused: vec![],
+        }
+    }
+
+    /// Enables fused execution of addition and multiplication loops in [`Machine::run_with`].
+    /// Must be called again after editing `program` directly.
+    pub fn optimize(&mut self) {
+        self.fused = optimize::analyze(&self.program);
+    }
+
+    pub fn is_optimized(&self) -> bool {
+        !self.fused.is_empty()
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.pc >= self.program.len()
+    }
+
+    /// Reads an operand.
+    pub fn value(&self, value: Value) -> W {
+        match value {
+            Value::Reg(r) => self[r],
+            Value::Imm(i) => W::from_i64(i),
+        }
+    }
+
+    /// Reads an operand used as a relative offset.
+    fn offset(&self, value: Value) -> isize {
+        match value {
+            Value::Reg(r) => self[r].to_i64() as isize,
+            Value::Imm(i) => i as isize,
+        }
+    }
+
+    /// Executes a single instruction, passing anything sent by `out` to `output`.
+    /// Returns why the machine stopped, if it did.
+    pub fn step(&mut self, output: &mut impl FnMut(W) -> Control) -> Option<Exit> {
+        let Some(&instruction) = self.program.get(self.pc) else {
+            return Some(Exit::Halted);
+        };
+
+        let mut next = self.pc.wrapping_add(1);
+        let mut exit = None;
+
+        match instruction {
+            Instruction::Cpy(x, Value::Reg(r)) => self[r] = self.value(x),
+            Instruction::Inc(Value::Reg(r)) => self[r] = self[r].wrapping_add(W::from_i64(1)),
+            Instruction::Dec(Value::Reg(r)) => self[r] = self[r].wrapping_sub(W::from_i64(1)),
+            Instruction::Jnz(x, y) => {
+                if !self.value(x).is_zero() {
+                    next = self.pc.wrapping_add_signed(self.offset(y));
+                }
+            }
+            Instruction::Tgl(x) => {
+                let target = self.pc.wrapping_add_signed(self.offset(x));
+                if let Some(i) = self.program.get_mut(target) {
+                    *i = i.toggle();
+                    if self.is_optimized() {
+                        self.optimize();
+                    }
+                }
+            }
+            Instruction::Out(x) => {
+                if output(self.value(x)) == Control::Stop {
+                    exit = Some(Exit::Stopped);
+                }
+            }
+            // invalid instructions produced by `tgl` are skipped.
+            Instruction::Cpy(_, Value::Imm(_))
+            | Instruction::Inc(Value::Imm(_))
+            | Instruction::Dec(Value::Imm(_)) => {}
+        }
+
+        self.pc = next;
+        self.steps += 1;
+        exit.or_else(|| self.is_halted().then_some(Exit::Halted))
+    }
+
+    /// Executes the loop starting at the current instruction in one go, if there is one that
+    /// takes at most `budget` steps. Returns whether it did.
+    pub(super) fn step_fused(&mut self, budget: Option<u64>) -> bool {
+        let Some(&Some(fused)) = self.fused.get(self.pc) else {
+            return false;
+        };
+        // loops whose counters start at zero or below wrap around, leave them to the interpreter.
+        let positive = |v: W| u64::try_from(v.to_i64()).ok().filter(|&n| n > 0);
+
+        let (target, delta, steps, subtract) = match fused {
+            Fused::Add {
+                target,
+                counter,
+                subtract,
+            } => {
+                let Some(n) = positive(self[counter]) else {
+                    return false;
+                };
+                (target, self[counter], n.checked_mul(3), subtract)
+            }
+            Fused::Mul {
+                target,
+                factor,
+                outer,
+                subtract,
+                ..
+            } => {
+                let (Some(f), Some(n)) = (positive(self.value(factor)), positive(self[outer]))
+                else {
+                    return false;
+                };
+                let steps = f
+                    .checked_mul(3)
+                    .and_then(|s| s.checked_add(3))
+                    .and_then(|s| s.checked_mul(n));
+                let delta = self.value(factor).wrapping_mul(self[outer]);
+                (target, delta, steps, subtract)
+            }
+        };
+
+        let Some(steps) = steps.filter(|&s| budget.is_none_or(|b| s <= b)) else {
+            return false;
+        };
+
+        match fused {
+            Fused::Add { counter, .. } => self[counter] = W::default(),
+            Fused::Mul { inner, outer, .. } => {
+                self[inner] = W::default();
+                self[outer] = W::default();
+            }
+        }
+        self[target] = if subtract {
+            self[target].wrapping_sub(delta)
+        } else {
+            self[target].wrapping_add(delta)
+        };
+        self.pc += fused.size();
+        self.steps += steps;
+        true
+    }
+
+    /// Runs until the program halts, ignoring any output.
+    pub fn run(&mut self) -> Exit {
+        self.run_with(None, |_| Control::Continue)
+    }
+
+    /// Runs until the program halts, `output` asks to stop, or `max_steps` instructions were executed.
+    pub fn run_with(
+        &mut self,
+        max_steps: Option<u64>,
+        mut output: impl FnMut(W) -> Control,
+    ) -> Exit {
+        let mut remaining = max_steps;
+        loop {
+            if remaining == Some(0) {
+                return Exit::StepLimit;
+            }
+            let before = self.steps;
+            if self.step_fused(remaining) {
+                if self.is_halted() {
+                    return Exit::Halted;
+                }
+            } else if let Some(exit) = self.step(&mut output) {
+                return exit;
+            }
+            if let Some(n) = remaining.as_mut() {
+                *n -= self.steps - before;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Control, Exit, Machine};
+    use crate::assembunny::{parse, Register};
+
+    #[test]
+    fn runs_until_halted() {
+        let program = parse("cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a\n").unwrap();
+        let mut machine = Machine::<u32>::new(program);
+        assert_eq!(machine.run(), Exit::Halted);
+        assert_eq!(machine[Register::A], 42);
+        assert_eq!(machine.steps, 5);
+    }
+
+    #[test]
+    fn toggles_and_skips_invalid_instructions() {
+        let program = parse("cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a\n").unwrap();
+        let mut machine = Machine::<i64>::new(program);
+        assert_eq!(machine.run(), Exit::Halted);
+        assert_eq!(machine[Register::A], 3);
+    }
+
+    #[test]
+    fn wraps_at_register_width() {
+        let program = parse("dec a\n").unwrap();
+        let mut machine = Machine::<u32>::new(program.clone());
+        machine.run();
+        assert_eq!(machine[Register::A], u32::MAX);
+
+        let mut machine = Machine::<i32>::new(program);
+        machine.run();
+        assert_eq!(machine[Register::A], -1);
+    }
+
+    #[test]
+    fn stops_at_step_limit_and_on_output() {
+        let program = parse("out a\ninc a\njnz 1 -2\n").unwrap();
+
+        let mut machine = Machine::<i64>::new(program.clone());
+        let mut output = vec![];
+        let exit = machine.run_with(Some(10), |v| {
+            output.push(v);
+            Control::Continue
+        });
+        assert_eq!(exit, Exit::StepLimit);
+        assert_eq!(machine.steps, 10);
+        assert_eq!(output, vec![0, 1, 2, 3]);
+
+        let mut machine = Machine::<i64>::new(program);
+        let exit = machine.run_with(None, |v| {
+            if v == 5 {
+                Control::Stop
+            } else {
+                Control::Continue
+            }
+        });
+        assert_eq!(exit, Exit::Stopped);
+        assert_eq!(machine.pc, 1);
+    }
+}
@@ -0,0 +1,232 @@
+//! The assembunny language used by days 12, 23 and 25.
+//!
+//! Programs are parsed with [`parse`] and executed on a [`Machine`], whose register width is
+//! picked through the [`Word`] type parameter. [`Machine::optimize`] speeds up the addition and
+//! multiplication loops most programs spend their time in, and [`compile::Compiled`] programs
+//! skip decoding instructions on every step. [`cfg::Cfg`] turns programs into control-flow graphs
+//! and pseudo-code. [`asm`] adds labels, comments and named constants on top of the plain syntax.
+
+pub mod asm;
+pub mod cfg;
+pub mod compile;
+mod machine;
+pub mod optimize;
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::error::{parse_lines, ParseError};
+
+pub use machine::{Control, Exit, Machine, Word};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Register {
+    A = 0,
+    B = 1,
+    C = 2,
+    D = 3,
+}
+
+impl Register {
+    pub const ALL: [Register; 4] = [Register::A, Register::B, Register::C, Register::D];
+}
+
+impl FromStr for Register {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "a" => Ok(Self::A),
+            "b" => Ok(Self::B),
+            "c" => Ok(Self::C),
+            "d" => Ok(Self::D),
+            _ => Err(format!("`{s}` is not a valid register")),
+        }
+    }
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Register::A => 'a',
+            Register::B => 'b',
+            Register::C => 'c',
+            Register::D => 'd',
+        };
+        write!(f, "{c}")
+    }
+}
+
+/// An operand: either a register or an immediate value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Value {
+    Reg(Register),
+    Imm(i64),
+}
+
+impl FromStr for Value {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(r) = s.parse() {
+            Ok(Self::Reg(r))
+        } else if let Ok(i) = s.parse() {
+            Ok(Self::Imm(i))
+        } else {
+            Err(format!("`{s}` must be a register or an integer"))
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Reg(r) => write!(f, "{r}"),
+            Value::Imm(i) => write!(f, "{i}"),
+        }
+    }
+}
+
+/// A single assembunny instruction.
+///
+/// Operands that must name a register are still stored as a [`Value`], because `tgl` can turn
+/// valid instructions into ones like `cpy 1 2` or `inc 3`. Those are skipped when executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    Cpy(Value, Value),
+    Inc(Value),
+    Dec(Value),
+    Jnz(Value, Value),
+    Tgl(Value),
+    Out(Value),
+}
+
+impl Instruction {
+    /// Returns the instruction `tgl` turns this one into.
+    pub fn toggle(&self) -> Self {
+        match *self {
+            Instruction::Inc(x) => Self::Dec(x),
+            Instruction::Dec(x) | Instruction::Tgl(x) | Instruction::Out(x) => Self::Inc(x),
+            Instruction::Jnz(x, y) => Self::Cpy(x, y),
+            Instruction::Cpy(x, y) => Self::Jnz(x, y),
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let op = parts.next().ok_or("empty instruction")?;
+        let args: Vec<&str> = parts.collect();
+
+        let arity = match op {
+            "cpy" | "jnz" => 2,
+            "inc" | "dec" | "tgl" | "out" => 1,
+            _ => {
+                return Err(
+                    ParseError::new(format!("`{op}` is not a valid instruction")).within(s, op),
+                )
+            }
+        };
+        if args.len() != arity {
+            let plural = if arity == 1 { "" } else { "s" };
+            return Err(ParseError::new(format!(
+                "`{op}` takes {arity} argument{plural}, found {}",
+                args.len()
+            )));
+        }
+
+        let register = |a: &str| {
+            a.parse::<Register>()
+                .map(Value::Reg)
+                .map_err(|e| ParseError::new(e).within(s, a))
+        };
+        let value = |a: &str| {
+            a.parse::<Value>()
+                .map_err(|e| ParseError::new(e).within(s, a))
+        };
+
+        Ok(match op {
+            "cpy" => Self::Cpy(value(args[0])?, register(args[1])?),
+            "inc" => Self::Inc(register(args[0])?),
+            "dec" => Self::Dec(register(args[0])?),
+            "jnz" => Self::Jnz(value(args[0])?, value(args[1])?),
+            "tgl" => Self::Tgl(value(args[0])?),
+            "out" => Self::Out(value(args[0])?),
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Cpy(x, y) => write!(f, "cpy {x} {y}"),
+            Instruction::Inc(x) => write!(f, "inc {x}"),
+            Instruction::Dec(x) => write!(f, "dec {x}"),
+            Instruction::Jnz(x, y) => write!(f, "jnz {x} {y}"),
+            Instruction::Tgl(x) => write!(f, "tgl {x}"),
+            Instruction::Out(x) => write!(f, "out {x}"),
+        }
+    }
+}
+
+/// Parses a program with one instruction per line. Blank lines are ignored.
+pub fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_lines(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Instruction, Register, Value};
+
+    #[test]
+    fn parses_all_instructions() {
+        let program = parse("cpy 41 a\ninc b\ndec c\njnz d -2\ntgl a\nout 1\n").unwrap();
+        assert_eq!(
+            program,
+            vec![
+                Instruction::Cpy(Value::Imm(41), Value::Reg(Register::A)),
+                Instruction::Inc(Value::Reg(Register::B)),
+                Instruction::Dec(Value::Reg(Register::C)),
+                Instruction::Jnz(Value::Reg(Register::D), Value::Imm(-2)),
+                Instruction::Tgl(Value::Reg(Register::A)),
+                Instruction::Out(Value::Imm(1)),
+            ]
+        );
+        let listing: Vec<String> = program.iter().map(ToString::to_string).collect();
+        assert_eq!(listing[3], "jnz d -2");
+    }
+
+    #[test]
+    fn reports_line_numbers() {
+        let err = parse("cpy 1 a\n\ninc 5\n").unwrap_err();
+        assert_eq!(err.line(), Some(3));
+        assert_eq!(err.column(), Some(5));
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 5: `5` is not a valid register"
+        );
+
+        let err = parse("jnz a\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: `jnz` takes 2 arguments, found 1");
+
+        let err = parse("cpy 1 a\nmul a b\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 1: `mul` is not a valid instruction"
+        );
+    }
+
+    #[test]
+    fn toggles_instructions() {
+        let a = Value::Reg(Register::A);
+        assert_eq!(Instruction::Inc(a).toggle(), Instruction::Dec(a));
+        assert_eq!(Instruction::Dec(a).toggle(), Instruction::Inc(a));
+        assert_eq!(Instruction::Tgl(a).toggle(), Instruction::Inc(a));
+        assert_eq!(Instruction::Out(a).toggle(), Instruction::Inc(a));
+        assert_eq!(Instruction::Jnz(a, a).toggle(), Instruction::Cpy(a, a));
+        assert_eq!(Instruction::Cpy(a, a).toggle(), Instruction::Jnz(a, a));
+    }
+}
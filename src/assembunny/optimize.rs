@@ -0,0 +1,265 @@
+//! Peephole optimization of the addition and multiplication loops assembunny programs are made of.
+//!
+//! [`analyze`] finds the loops, and a [`Machine`](super::Machine) with optimizations enabled runs
+//! each one as a single fused operation whenever execution reaches its first instruction.
+
+use super::{Instruction, Register, Value};
+
+/// A loop that can be executed in one go.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fused {
+    /// `target += counter; counter = 0`, or `-=` if `subtract` is set.
+    ///
+    /// ```text
+    /// inc target
+    /// dec counter
+    /// jnz counter -2
+    /// ```
+    Add {
+        target: Register,
+        counter: Register,
+        subtract: bool,
+    },
+    /// `target += factor * outer; inner = 0; outer = 0`, or `-=` if `subtract` is set.
+    ///
+    /// ```text
+    /// cpy factor inner
+    /// inc target
+    /// dec inner
+    /// jnz inner -2
+    /// dec outer
+    /// jnz outer -5
+    /// ```
+    Mul {
+        target: Register,
+        factor: Value,
+        inner: Register,
+        outer: Register,
+        subtract: bool,
+    },
+}
+
+impl Fused {
+    /// Number of instructions covered by the loop.
+    pub fn size(&self) -> usize {
+        match self {
+            Fused::Add { .. } => 3,
+            Fused::Mul { .. } => 6,
+        }
+    }
+}
+
+/// Returns, for every instruction, the loop starting at it, if any.
+pub fn analyze(program: &[Instruction]) -> Vec<Option<Fused>> {
+    (0..program.len())
+        .map(|pc| match_mul(&program[pc..]).or_else(|| match_add(&program[pc..])))
+        .collect()
+}
+
+fn match_add(code: &[Instruction]) -> Option<Fused> {
+    let [first, second, Instruction::Jnz(Value::Reg(jump), Value::Imm(-2)), ..] = *code else {
+        return None;
+    };
+
+    let (target, counter, subtract) = match (first, second) {
+        (Instruction::Inc(Value::Reg(t)), Instruction::Dec(Value::Reg(c)))
+        | (Instruction::Dec(Value::Reg(c)), Instruction::Inc(Value::Reg(t))) => (t, c, false),
+        (Instruction::Dec(Value::Reg(t)), Instruction::Dec(Value::Reg(c))) if c == jump => {
+            (t, c, true)
+        }
+        (Instruction::Dec(Value::Reg(c)), Instruction::Dec(Value::Reg(t))) => (t, c, true),
+        _ => return None,
+    };
+
+    (counter == jump && target != counter).then_some(Fused::Add {
+        target,
+        counter,
+        subtract,
+    })
+}
+
+fn match_mul(code: &[Instruction]) -> Option<Fused> {
+    let [Instruction::Cpy(factor, Value::Reg(inner)), _, _, _, Instruction::Dec(Value::Reg(outer)), Instruction::Jnz(Value::Reg(jump), Value::Imm(-5)), ..] =
+        *code
+    else {
+        return None;
+    };
+
+    let Some(Fused::Add {
+        target,
+        counter,
+        subtract,
+    }) = match_add(&code[1..])
+    else {
+        return None;
+    };
+
+    let distinct = counter == inner && outer == jump && target != outer && inner != outer;
+    let constant_factor = match factor {
+        Value::Reg(r) => r != target && r != inner && r != outer,
+        Value::Imm(_) => true,
+    };
+
+    (distinct && constant_factor).then_some(Fused::Mul {
+        target,
+        factor,
+        inner,
+        outer,
+        subtract,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{analyze, Fused};
+    use crate::{
+        assembunny::{parse, Control, Instruction, Machine, Register, Value},
+        random::Lcg,
+    };
+
+    #[test]
+    fn finds_loops() {
+        let program = parse(
+            "cpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5\ndec b\ndec c\njnz c -2\ndec a\ndec a\njnz a -2\n",
+        )
+        .unwrap();
+        let fused = analyze(&program);
+        assert_eq!(
+            fused[0],
+            Some(Fused::Mul {
+                target: Register::A,
+                factor: Value::Reg(Register::B),
+                inner: Register::C,
+                outer: Register::D,
+                subtract: false,
+            })
+        );
+        assert_eq!(
+            fused[1],
+            Some(Fused::Add {
+                target: Register::A,
+                counter: Register::C,
+                subtract: false,
+            })
+        );
+        assert_eq!(
+            fused[6],
+            Some(Fused::Add {
+                target: Register::B,
+                counter: Register::C,
+                subtract: true,
+            })
+        );
+        // `dec a; dec a; jnz a -2` only counts down a single register.
+        assert_eq!(fused[9], None);
+        assert_eq!(fused.iter().flatten().count(), 3);
+    }
+
+    /// Runs the program with and without optimizations and checks that both end in the same state.
+    fn assert_same(program: &[Instruction], registers: [i64; 4], max_steps: Option<u64>) {
+        let run = |optimize: bool| {
+            let mut machine = Machine::with_registers(program.to_vec(), registers);
+            if optimize {
+                machine.optimize();
+            }
+            let mut output = vec![];
+            let exit = machine.run_with(max_steps, |v| {
+                output.push(v);
+                Control::Continue
+            });
+            (
+                exit,
+                machine.registers,
+                machine.pc,
+                machine.steps,
+                machine.program,
+                output,
+            )
+        };
+        assert_eq!(run(true), run(false), "{registers:?} {max_steps:?}");
+    }
+
+    #[test]
+    fn matches_interpreter_on_loops() {
+        let program = parse(
+            "cpy 3 d\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5\nout a\ndec a\ndec b\njnz b -2\nout a\n",
+        )
+        .unwrap();
+        for b in [1, 2, 7, 40] {
+            assert_same(&program, [0, b, 0, 0], None);
+            // step limits that end inside and right after the loops.
+            for limit in [0, 1, 5, 20, 100, 3 * b as u64 + 40] {
+                assert_same(&program, [0, b, 0, 0], Some(limit));
+            }
+        }
+    }
+
+    #[test]
+    fn matches_interpreter_when_tgl_rewrites_a_loop() {
+        // the `tgl` turns `jnz c -2` into `cpy c -2` after the first pass, and back on the second.
+        let program =
+            parse("cpy 2 d\ncpy 5 c\ninc a\ndec c\njnz c -2\ncpy -2 b\ntgl b\ndec d\njnz d -7\n")
+                .unwrap();
+        assert_same(&program, [0, 0, 0, 0], None);
+
+        let mut machine = Machine::<i64>::new(program);
+        machine.optimize();
+        machine.run();
+        assert_eq!(machine[Register::A], 6);
+    }
+
+    /// Generates random programs made of loops, jumps, `tgl` and `out`, with initial registers
+    /// and a step limit, for comparing against the plain interpreter.
+    pub(crate) fn random_programs(count: usize) -> Vec<(Vec<Instruction>, [i64; 4], u64)> {
+        let mut lcg = Lcg::new(0x2016);
+        let mut random = |n: u64| lcg.below(n);
+
+        let registers = ["a", "b", "c", "d"];
+        (0..count)
+            .map(|_| {
+                let mut lines = vec![];
+                while lines.len() < 16 {
+                    let r = |i: u64| registers[i as usize];
+                    let (x, y, z) = (r(random(4)), r(random(4)), r(random(4)));
+                    match random(9) {
+                        0 => lines.push(format!("cpy {} {x}", random(6))),
+                        1 => lines.extend([
+                            format!("inc {x}"),
+                            format!("dec {y}"),
+                            format!("jnz {y} -2"),
+                        ]),
+                        2 => lines.extend([
+                            format!("dec {y}"),
+                            format!("dec {x}"),
+                            format!("jnz {y} -2"),
+                        ]),
+                        3 => lines.extend([
+                            format!("cpy {} {y}", random(5)),
+                            format!("inc {x}"),
+                            format!("dec {y}"),
+                            format!("jnz {y} -2"),
+                            format!("dec {z}"),
+                            format!("jnz {z} -5"),
+                        ]),
+                        4 => lines.push(format!("tgl {}", random(8))),
+                        5 => lines.push(format!("jnz {x} -{}", random(4))),
+                        6 => lines.push(format!("out {x}")),
+                        7 => lines.push(format!("jnz {x} {y}")),
+                        _ => lines.push(format!("inc {x}")),
+                    }
+                }
+                let program = parse(&lines.join("\n")).unwrap();
+                let registers = [random(5), random(5), random(5), random(5)].map(|v| v as i64);
+                (program, registers, random(2000))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_interpreter_on_random_programs() {
+        for (program, registers, max_steps) in random_programs(300) {
+            assert_same(&program, registers, Some(2000));
+            assert_same(&program, registers, Some(max_steps));
+        }
+    }
+}
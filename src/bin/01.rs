@@ -1,5 +1,10 @@
 use std::{collections::HashSet, ops::AddAssign, str::FromStr};
 
+use advent_of_code::{
+    error::{parse_in, parse_separated, ParseError},
+    visualize,
+};
+
 advent_of_code::solution!(1);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -31,56 +36,150 @@ impl Default for Loc {
     }
 }
 
-impl Loc {
-    const fn taxicab_len(&self) -> u32 {
-        self.x.unsigned_abs() + self.y.unsigned_abs()
+type Pos = (i32, i32);
+
+const fn taxicab_len((x, y): Pos) -> u32 {
+    x.unsigned_abs() + y.unsigned_abs()
+}
+
+/// A straight piece of the walk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Segment {
+    from: Pos,
+    to: Pos,
+}
+
+impl Segment {
+    const fn len(&self) -> u32 {
+        self.from.0.abs_diff(self.to.0) + self.from.1.abs_diff(self.to.1)
     }
 
-    fn process(&mut self, instructions: &[Instruction]) -> u32 {
-        for inst in instructions {
-            *self += inst;
-        }
-        self.taxicab_len()
-    }
-
-    fn processv2(&mut self, instructions: &[Instruction]) -> u32 {
-        let mut points_seen = HashSet::with_capacity(instructions.len());
-        for inst in instructions {
-            let mut dir = self.dir;
-            dir += inst.turn;
-            match dir {
-                Dir::N => {
-                    for y in 0..(inst.len) {
-                        if !points_seen.insert((self.x, self.y + y)) {
-                            return self.x.unsigned_abs() + (self.y + y).unsigned_abs();
-                        }
-                    }
-                }
-                Dir::S => {
-                    for y in 0..(inst.len) {
-                        if !points_seen.insert((self.x, self.y - y)) {
-                            return self.x.unsigned_abs() + (self.y - y).unsigned_abs();
-                        }
-                    }
-                }
-                Dir::E => {
-                    for x in 0..(inst.len) {
-                        if !points_seen.insert((self.x + x, self.y)) {
-                            return (self.x + x).unsigned_abs() + self.y.unsigned_abs();
-                        }
-                    }
-                }
-                Dir::W => {
-                    for x in 0..(inst.len) {
-                        if !points_seen.insert((self.x - x, self.y)) {
-                            return (self.x - x).unsigned_abs() + self.y.unsigned_abs();
-                        }
-                    }
+    /// Every block walked along the segment, excluding `from` and including `to`.
+    fn blocks(self) -> impl Iterator<Item = Pos> {
+        let (dx, dy) = (
+            (self.to.0 - self.from.0).signum(),
+            (self.to.1 - self.from.1).signum(),
+        );
+        (1..=self.len() as i32).map(move |i| (self.from.0 + dx * i, self.from.1 + dy * i))
+    }
+}
+
+/// The route from the start to Easter Bunny HQ as a polyline, with north pointing to +y.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Path {
+    segments: Vec<Segment>,
+}
+
+impl Path {
+    fn trace(instructions: &[Instruction]) -> Self {
+        let mut loc = Loc::default();
+        let segments = instructions
+            .iter()
+            .map(|inst| {
+                let from = (loc.x, loc.y);
+                loc += inst;
+                Segment {
+                    from,
+                    to: (loc.x, loc.y),
                 }
+            })
+            .collect();
+        Self { segments }
+    }
+
+    /// Where the instructions lead, i.e. HQ according to part one.
+    fn end(&self) -> Pos {
+        self.segments.last().map_or((0, 0), |s| s.to)
+    }
+
+    /// The number of blocks walked in total.
+    fn distance(&self) -> u32 {
+        self.segments.iter().map(Segment::len).sum()
+    }
+
+    /// The lowest and highest coordinates reached.
+    fn bounds(&self) -> (Pos, Pos) {
+        self.segments
+            .iter()
+            .fold(((0, 0), (0, 0)), |(min, max), s| {
+                (
+                    (min.0.min(s.to.0), min.1.min(s.to.1)),
+                    (max.0.max(s.to.0), max.1.max(s.to.1)),
+                )
+            })
+    }
+
+    /// Every block walked on again, in the order of the walk. The first one is HQ according to
+    /// part two.
+    fn intersections(&self) -> Vec<Pos> {
+        let mut seen = HashSet::from([(0, 0)]);
+        self.segments
+            .iter()
+            .flat_map(|s| s.blocks())
+            .filter(|&p| !seen.insert(p))
+            .collect()
+    }
+
+    /// The statistics of the route, one per line.
+    fn summary(&self) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds();
+        let intersections: Vec<String> = self
+            .intersections()
+            .iter()
+            .map(|(x, y)| format!("{x},{y}"))
+            .collect();
+        format!(
+            "Walked {} blocks in {} segments\n\
+             Bounds: {min_x},{min_y} to {max_x},{max_y}\n\
+             HQ: {},{} ({} blocks away)\n\
+             Intersections: {}\n",
+            self.distance(),
+            self.segments.len(),
+            self.end().0,
+            self.end().1,
+            taxicab_len(self.end()),
+            if intersections.is_empty() {
+                "none".into()
+            } else {
+                intersections.join(" ")
             }
-            *self += inst;
+        )
+    }
+
+    /// Draws the route with the start in green, HQ in red and the intersections in orange.
+    fn svg(&self) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds();
+        let radius = (f64::from((max_x - min_x).max(max_y - min_y)) / 100.0).max(0.2);
+        let (width, height) = (max_x - min_x + 2, max_y - min_y + 2);
+        // svg's y axis points down, so north is flipped up.
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {width} {height}\">\n",
+            min_x - 1,
+            -max_y - 1
+        );
+        svg += &format!(
+            "  <title>{} blocks walked, HQ is {} blocks away</title>\n",
+            self.distance(),
+            taxicab_len(self.end())
+        );
+        svg += "  <polyline fill=\"none\" stroke=\"black\" stroke-width=\"1\" \
+                vector-effect=\"non-scaling-stroke\" points=\"0,0";
+        for s in &self.segments {
+            svg += &format!(" {},{}", s.to.0, -s.to.1);
+        }
+        svg += "\"/>\n";
+        let mut circle = |(x, y): Pos, color: &str| {
+            svg += &format!(
+                "  <circle cx=\"{x}\" cy=\"{}\" r=\"{radius}\" fill=\"{color}\"/>\n",
+                -y
+            );
+        };
+        for p in self.intersections() {
+            circle(p, "orange");
         }
-        0
+        circle((0, 0), "green");
+        circle(self.end(), "red");
+        svg + "</svg>\n"
     }
 }
 
@@ -108,23 +207,23 @@ impl AddAssign<Turn> for Dir {
 }
 
 impl FromStr for Turn {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "L" => Ok(Turn::L),
             "R" => Ok(Turn::R),
-            _ => Err(()),
+            _ => Err(ParseError::expected("`L` or `R`", s)),
         }
     }
 }
 
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (turn, len) = s.split_at(1);
-        let turn = turn.parse()?;
-        let len = len.parse().map_err(|_| ())?;
+        let (turn, len) = s.split_at(s.chars().next().map_or(0, char::len_utf8));
+        let turn = turn.parse::<Turn>().map_err(|e| e.within(s, turn))?;
+        let len = parse_in(s, len, "a distance")?;
         Ok(Self { turn, len })
     }
 }
@@ -137,24 +236,22 @@ enum Dir {
     W,
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut loc = Loc::default();
-    let directions = input
-        .trim()
-        .split(", ")
-        .map(|s| s.parse().unwrap())
-        .collect::<Vec<_>>();
-    Some(loc.process(&directions))
+fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    let line = input.trim();
+    parse_separated(line, ", ").map_err(|e| e.at_line(1, line))
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut loc = Loc::default();
-    let directions = input
-        .trim()
-        .split(", ")
-        .map(|s| s.parse().unwrap())
-        .collect::<Vec<_>>();
-    Some(loc.processv2(&directions))
+pub fn part_one(input: &str) -> Result<u32, ParseError> {
+    Ok(taxicab_len(Path::trace(&parse(input)?).end()))
+}
+
+pub fn part_two(input: &str) -> Result<Option<u32>, ParseError> {
+    let path = Path::trace(&parse(input)?);
+    if let Some(mut v) = visualize::start() {
+        eprint!("{}", path.summary());
+        v.save("svg", &path.svg());
+    }
+    Ok(path.intersections().first().copied().map(taxicab_len))
 }
 
 #[cfg(test)]
@@ -166,7 +263,7 @@ mod tests {
         let result = part_one(&advent_of_code::template::read_file_part(
             "examples", DAY, 1,
         ));
-        assert_eq!(result, Some(5));
+        assert_eq!(result, Ok(5));
     }
 
     #[test]
@@ -174,7 +271,7 @@ mod tests {
         let result = part_one(&advent_of_code::template::read_file_part(
             "examples", DAY, 2,
         ));
-        assert_eq!(result, Some(2));
+        assert_eq!(result, Ok(2));
     }
 
     #[test]
@@ -182,7 +279,7 @@ mod tests {
         let result = part_one(&advent_of_code::template::read_file_part(
             "examples", DAY, 3,
         ));
-        assert_eq!(result, Some(12));
+        assert_eq!(result, Ok(12));
     }
 
     #[test]
@@ -190,6 +287,28 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file_part(
             "examples", DAY, 4,
         ));
-        assert_eq!(result, Some(4));
+        assert_eq!(result, Ok(Some(4)));
+    }
+
+    #[test]
+    fn test_path() {
+        let path = Path::trace(&parse("R8, R4, R4, R8, L4, L1, L4").unwrap());
+        assert_eq!(path.end(), (4, 3));
+        assert_eq!(path.distance(), 33);
+        assert_eq!(path.bounds(), ((0, -4), (8, 4)));
+        assert_eq!(path.intersections(), vec![(4, 0), (4, 3)]);
+        assert_eq!(
+            path.summary(),
+            "Walked 33 blocks in 7 segments\n\
+             Bounds: 0,-4 to 8,4\n\
+             HQ: 4,3 (7 blocks away)\n\
+             Intersections: 4,0 4,3\n"
+        );
+
+        let svg = path.svg();
+        assert!(svg.contains("viewBox=\"-1 -5 10 10\""));
+        assert!(svg.contains("points=\"0,0 8,0 8,4 4,4 4,-4 0,-4 0,-3 4,-3\""));
+        assert!(svg.contains("<title>33 blocks walked, HQ is 7 blocks away</title>"));
+        assert_eq!(svg.matches("<circle").count(), 4);
     }
 }
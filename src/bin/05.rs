@@ -1,84 +1,221 @@
-use md5::{Digest, Md5};
+use advent_of_code::{
+    grid::Point,
+    mining::{leading_zero_nibbles, nibble, Miner},
+    random::Lcg,
+    visualize::{self, Frame, Rgb, BACKGROUND},
+};
 
 advent_of_code::solution!(5);
 
-pub fn part_one(input: &str) -> Option<String> {
-    let mut i = 0;
-    let mut out = String::with_capacity(8);
-    let input = input.trim();
-    let binput = input.as_bytes();
-    while out.len() < 8 {
-        let mut hasher = Md5::new();
-        hasher.update(binput);
-        let num_str = i.to_string();
-        hasher.update(num_str.as_bytes());
-        let done = hasher.finalize();
-        if done[0] == 0 && done[1] == 0 && done[2] < 16 {
-            out.push_str(&format!("{:x}", done[2] & 0b00001111))
+/// The digits 0-9 and a-f in a 3×5 font, one row of three pixels per byte.
+const FONT: [[u8; 5]; 16] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+    [0b111, 0b101, 0b111, 0b101, 0b101],
+    [0b110, 0b101, 0b110, 0b101, 0b110],
+    [0b111, 0b100, 0b100, 0b100, 0b111],
+    [0b110, 0b101, 0b101, 0b101, 0b110],
+    [0b111, 0b100, 0b111, 0b100, 0b111],
+    [0b111, 0b100, 0b111, 0b100, 0b100],
+];
+
+const FOUND: Rgb = [80, 220, 120];
+const GUESS: Rgb = [70, 70, 90];
+
+/// The number of leading zeros that make a hash interesting.
+const ZEROS: u32 = 5;
+
+/// The two hex digits after the leading zeros of every hash that starts with `zeros` zeros.
+fn interesting(miner: &Miner, zeros: u32) -> impl Iterator<Item = (u8, u8)> + '_ {
+    let i = zeros as usize;
+    miner
+        .hits(0, move |_, d| {
+            (leading_zero_nibbles(d) >= zeros).then(|| (nibble(d, i), nibble(d, i + 1)))
+        })
+        .map(|(_, hit)| hit)
+}
+
+fn hex_digit(n: u8) -> char {
+    char::from_digit(n.into(), 16).expect("nibbles are below 16")
+}
+
+/// Both passwords as far as they are known, with random digits in the unknown positions.
+#[derive(Debug, Default)]
+struct Passwords {
+    first: Vec<u8>,
+    second: [Option<u8>; 8],
+    /// Stand-ins for the unknown digits, re-rolled for every frame.
+    noise: [[u8; 8]; 2],
+    random: Lcg,
+}
+
+impl Passwords {
+    /// Adds the digits of the next interesting hash. Hashes must come in index order, as both
+    /// passwords depend on which hash came first.
+    fn push(&mut self, (sixth, seventh): (u8, u8)) {
+        if self.first.len() < 8 {
+            self.first.push(sixth);
+        }
+        if let Some(slot @ None) = self.second.get_mut(usize::from(sixth)) {
+            *slot = Some(seventh);
+        }
+    }
+
+    fn first(&self) -> Option<String> {
+        (self.first.len() == 8).then(|| self.first.iter().copied().map(hex_digit).collect())
+    }
+
+    fn second(&self) -> Option<String> {
+        self.second.iter().map(|d| d.map(hex_digit)).collect()
+    }
+
+    fn reroll(&mut self) {
+        for digit in self.noise.iter_mut().flatten() {
+            *digit = self.random.below(16) as u8;
         }
-        i += 1;
     }
-    Some(out)
+
+    /// The digit shown at `pos` of a password, and whether it is known.
+    fn digit(&self, password: usize, pos: usize) -> (u8, bool) {
+        let known = match password {
+            0 => self.first.get(pos).copied(),
+            _ => self.second[pos],
+        };
+        known.map_or((self.noise[password][pos], false), |d| (d, true))
+    }
 }
 
-pub fn part_two(input: &str) -> Option<String> {
-    let mut i = 0;
-    let mut out = ['X'; 8];
-    let mut ctr = 0;
-    let input = input.trim();
-    let binput = input.as_bytes();
-    while ctr < 8 {
-        let mut hasher = Md5::new();
-        hasher.update(binput);
-        let num_str = i.to_string();
-        hasher.update(num_str.as_bytes());
-        let done = hasher.finalize();
-        if done[0] == 0 && done[1] == 0 && done[2] < 16 {
-            let i = done[2] & 0b00001111;
-            if i < 8 {
-                let pos = &mut out[i as usize];
-                if pos == &'X' {
-                    match (done[3] & 0b11110000) >> 4 {
-                        0b0000 => *pos = '0',
-                        0b0001 => *pos = '1',
-                        0b0010 => *pos = '2',
-                        0b0011 => *pos = '3',
-                        0b0100 => *pos = '4',
-                        0b0101 => *pos = '5',
-                        0b0110 => *pos = '6',
-                        0b0111 => *pos = '7',
-                        0b1000 => *pos = '8',
-                        0b1001 => *pos = '9',
-                        0b1010 => *pos = 'a',
-                        0b1011 => *pos = 'b',
-                        0b1100 => *pos = 'c',
-                        0b1101 => *pos = 'd',
-                        0b1110 => *pos = 'e',
-                        0b1111 => *pos = 'f',
-                        _ => {}
-                    }
-                    ctr += 1;
-                }
+impl Frame for Passwords {
+    fn size(&self) -> (usize, usize) {
+        (8 * 4 + 1, 2 * 6 + 1)
+    }
+
+    fn pixel(&self, (x, y): Point) -> Rgb {
+        // digits are four pixels apart, with a one pixel border around them.
+        if x == 0 || y == 0 {
+            return BACKGROUND;
+        }
+        let (pos, dx) = ((x - 1) / 4, (x - 1) % 4);
+        let (password, dy) = ((y - 1) / 6, (y - 1) % 6);
+        if dx == 3 || dy == 5 || pos >= 8 || password >= 2 {
+            return BACKGROUND;
+        }
+        let (digit, known) = self.digit(password, pos);
+        if FONT[usize::from(digit)][dy] & (0b100 >> dx) == 0 {
+            BACKGROUND
+        } else if known {
+            FOUND
+        } else {
+            GUESS
+        }
+    }
+
+    fn text(&self) -> String {
+        let mut text = String::new();
+        for password in 0..2 {
+            text += &format!("Part {}: ", password + 1);
+            for pos in 0..8 {
+                let (digit, known) = self.digit(password, pos);
+                let style = if known { "\x1b[1;32m" } else { "\x1b[2m" };
+                text += &format!("{style}{}\x1b[0m", hex_digit(digit));
             }
+            text += "\n";
         }
-        i += 1;
+        text
     }
-    Some(out.iter().collect())
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//
-//     #[test]
-//     fn test_part_one() {
-//         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-//         assert_eq!(result, Some("18f47a30".into()));
-//     }
-//
-//     #[test]
-//     fn test_part_two() {
-//         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-//         assert_eq!(result, Some("05ace8e3".into()));
-//     }
-// }
+/// Fills in both passwords in a single pass over the hashes, until `done` is satisfied.
+fn decrypt(input: &str, zeros: u32, done: impl Fn(&Passwords) -> bool) -> Passwords {
+    let miner = Miner::new(input.trim());
+    let mut visualizer = visualize::start();
+    let mut passwords = Passwords::default();
+    for hit in interesting(&miner, zeros) {
+        passwords.push(hit);
+        if let Some(v) = &mut visualizer {
+            // a few frames per hit, so the unknown digits flicker.
+            for _ in 0..3 {
+                passwords.reroll();
+                v.frame(&passwords);
+            }
+        }
+        if done(&passwords) {
+            break;
+        }
+    }
+    passwords
+}
+
+pub fn part_one(input: &str) -> Option<String> {
+    decrypt(input, ZEROS, |p| p.first().is_some()).first()
+}
+
+pub fn part_two(input: &str) -> Option<String> {
+    decrypt(input, ZEROS, |p| p.second().is_some()).second()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passwords() {
+        let mut passwords = Passwords::default();
+        for hit in [(1, 5), (4, 0xe), (9, 0), (4, 3), (0, 0xa)] {
+            passwords.push(hit);
+        }
+        assert_eq!(passwords.first, [1, 4, 9, 4, 0]);
+        assert_eq!(passwords.digit(1, 4), (0xe, true));
+        assert!(!passwords.digit(1, 2).1);
+        for hit in [(2, 1), (3, 2), (5, 3), (6, 4), (7, 6)] {
+            passwords.push(hit);
+        }
+        assert_eq!(passwords.first(), Some("14940235".into()));
+        assert_eq!(passwords.second(), Some("a512e346".into()));
+    }
+
+    #[test]
+    fn test_frame() {
+        let mut passwords = Passwords::default();
+        passwords.push((0, 0xc));
+        assert_eq!(passwords.size(), (33, 13));
+        // the top row of the known `c` in the second password.
+        assert_eq!(passwords.pixel((1, 7)), FOUND);
+        assert_eq!(passwords.pixel((3, 7)), FOUND);
+        assert_eq!(passwords.pixel((4, 7)), BACKGROUND);
+        assert!(passwords.text().starts_with("Part 1: \x1b[1;32m0\x1b[0m"));
+    }
+
+    #[test]
+    fn test_decrypt() {
+        // three zeros keep the debug build fast; the digits are looked up one by one here.
+        let hits: Vec<(u8, u8)> = (0..)
+            .map(|i| advent_of_code::mining::hash(b"abc", i, 0))
+            .filter(|d| leading_zero_nibbles(d) >= 3)
+            .map(|d| (nibble(&d, 3), nibble(&d, 4)))
+            .take(100)
+            .collect();
+        let first: String = hits[..8].iter().map(|&(d, _)| hex_digit(d)).collect();
+        let mut second = [None; 8];
+        for &(pos, d) in &hits {
+            if let Some(slot @ None) = second.get_mut(usize::from(pos)) {
+                *slot = Some(hex_digit(d));
+            }
+        }
+        let second: Option<String> = second.into_iter().collect();
+
+        let passwords = decrypt("abc\n", 3, |p| p.first().is_some());
+        assert_eq!(passwords.first(), Some(first));
+        let passwords = decrypt("abc\n", 3, |p| p.second().is_some());
+        assert_eq!(passwords.second(), second);
+        assert!(passwords.second().is_some());
+    }
+}
@@ -1,5 +1,11 @@
 use std::str::FromStr;
 
+use advent_of_code::{
+    error::{parse_in, parse_lines, ParseError},
+    grid::BitGrid,
+    visualize,
+};
+
 advent_of_code::solution!(8);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,124 +21,84 @@ enum Instruction {
 }
 
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts = s.split_whitespace().collect::<Vec<_>>();
-        match parts[0] {
+        // missing parts point at the end of the line.
+        let part = |i: usize| parts.get(i).copied().unwrap_or(&s[s.len()..]);
+        match part(0) {
             "rect" => {
-                let (x, y) = parts[1]
+                let (x, y) = part(1)
                     .split_once('x')
-                    .map(|(x, y)| (x.parse().unwrap(), y.parse().unwrap()))
-                    .ok_or(())?;
-                Ok(Self::Rect { x, y })
+                    .ok_or_else(|| ParseError::expected("`<x>x<y>`", part(1)).within(s, part(1)))?;
+                Ok(Self::Rect {
+                    x: parse_in(s, x, "a width")?,
+                    y: parse_in(s, y, "a height")?,
+                })
             }
             "rotate" => {
-                let axis = match parts[1] {
+                let axis = match part(1) {
                     "row" => Axis::Row,
                     "column" => Axis::Column,
-                    _ => return Err(()),
+                    other => {
+                        return Err(
+                            ParseError::expected("`row` or `column`", other).within(s, other)
+                        )
+                    }
                 };
-                let coord = parts[2]
-                    .split_once('=')
-                    .map(|(_, c)| c.parse().unwrap())
-                    .ok_or(())?;
-                let by = parts[4].parse().map_err(|_| ())?;
-                Ok(Self::Rotate { axis, coord, by })
+                let (_, coord) = part(2).split_once('=').ok_or_else(|| {
+                    ParseError::expected("`x=<n>` or `y=<n>`", part(2)).within(s, part(2))
+                })?;
+                Ok(Self::Rotate {
+                    axis,
+                    coord: parse_in(s, coord, "a coordinate")?,
+                    by: parse_in(s, part(4), "a distance")?,
+                })
             }
-            _ => Err(()),
+            other => Err(ParseError::expected("`rect` or `rotate`", other).within(s, other)),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Display([[bool; 50]; 6]);
-
-impl std::fmt::Display for Display {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for line in self.0 {
-            for item in line.iter().map(|l| if *l { 'X' } else { '.' }) {
-                write!(f, "{item}")?;
-            }
-            writeln!(f)?;
+const WIDTH: usize = 50;
+const HEIGHT: usize = 6;
+
+fn process(instructions: &[Instruction]) -> BitGrid {
+    let mut screen = BitGrid::new(WIDTH, HEIGHT);
+    let mut visualizer = visualize::start();
+    for inst in instructions {
+        match *inst {
+            Instruction::Rect { x, y } => (0..y)
+                .flat_map(|y| (0..x).map(move |x| (x, y)))
+                .for_each(|p| screen.set(p, true)),
+            Instruction::Rotate {
+                axis: Axis::Row,
+                coord,
+                by,
+            } => screen.rotate_row(coord, by),
+            Instruction::Rotate {
+                axis: Axis::Column,
+                coord,
+                by,
+            } => screen.rotate_column(coord, by),
         }
-        Ok(())
-    }
-}
-
-impl Display {
-    fn rect(&mut self, x: usize, y: usize) {
-        self.0[..y]
-            .iter_mut()
-            .for_each(|xs| xs[..x].iter_mut().for_each(|x| *x = true));
-    }
-
-    fn rotate_column(&mut self, x: usize, step: usize) {
-        let column: [bool; 6] = self
-            .0
-            .iter()
-            .map(|xs| xs[x])
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
-        self.0
-            .iter_mut()
-            .enumerate()
-            .for_each(|(i, xs)| xs[x] = column[(i + 6 - step) % 6])
-    }
-
-    fn rotate_row(&mut self, y: usize, step: usize) {
-        let row = self.0[y];
-        self.0[y]
-            .iter_mut()
-            .enumerate()
-            .for_each(|(i, x)| *x = row[(i + 50 - step) % 50])
-    }
-
-    fn process(&mut self, instructions: &[Instruction]) {
-        // println!("{self}");
-        for inst in instructions {
-            match inst {
-                Instruction::Rect { x, y } => self.rect(*x, *y),
-                Instruction::Rotate {
-                    axis: Axis::Row,
-                    coord,
-                    by,
-                } => self.rotate_row(*coord, *by),
-                Instruction::Rotate {
-                    axis: Axis::Column,
-                    coord,
-                    by,
-                } => self.rotate_column(*coord, *by),
-            }
-            // println!("{self}");
+        if let Some(v) = &mut visualizer {
+            v.frame(&screen);
         }
     }
-
-    fn count(&self) -> u32 {
-        self.0.iter().flatten().filter(|&p| *p).count() as u32
-    }
-}
-
-impl Default for Display {
-    fn default() -> Self {
-        Self([[false; 50]; 6])
-    }
+    screen
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let instructions: Vec<_> = input.lines().map(|l| l.parse().unwrap()).collect();
-    let mut disp = Display::default();
-    disp.process(&instructions);
-    Some(disp.count())
+pub fn part_one(input: &str) -> Result<u32, ParseError> {
+    let instructions = parse_lines(input)?;
+    Ok(process(&instructions).count_ones() as u32)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let instructions: Vec<_> = input.lines().map(|l| l.parse().unwrap()).collect();
-    let mut disp = Display::default();
-    disp.process(&instructions);
-    println!("{disp}");
-    Some(0)
+pub fn part_two(input: &str) -> Result<u32, ParseError> {
+    let instructions = parse_lines(input)?;
+    println!("{}", process(&instructions));
+    Ok(0)
 }
 
 #[cfg(test)]
@@ -142,6 +108,6 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(6));
+        assert_eq!(result, Ok(6));
     }
 }
@@ -1,13 +1,45 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::{cmp::Ordering, collections::HashMap, str::FromStr};
+
+use advent_of_code::parse::{map_lines, ParseError, Pattern};
 
 advent_of_code::solution!(10);
 
+const VALUE: Pattern = Pattern::new("value {} goes to bot {}");
+const GIVES: Pattern = Pattern::new("bot {} gives low to {} {} and high to {} {}");
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Destination {
     Bot(u32),
     Output(u32),
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Bot,
+    Output,
+}
+
+impl FromStr for Kind {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bot" => Ok(Self::Bot),
+            "output" => Ok(Self::Output),
+            _ => Err(ParseError::expected("`bot` or `output`", s)),
+        }
+    }
+}
+
+impl Destination {
+    fn new(kind: Kind, n: u32) -> Self {
+        match kind {
+            Kind::Bot => Self::Bot(n),
+            Kind::Output => Self::Output(n),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 struct Bot {
     low: Destination,
@@ -218,120 +250,64 @@ impl Map {
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut bots = HashMap::new();
-    let mut outputs = HashMap::new();
-    let mut starting_values = HashMap::new();
-    for line in input.lines() {
-        if line.contains("gives") {
-            let words = line.split_whitespace().collect::<Vec<_>>();
-            let (dest_a, dest_b) = match (words[5], words[10]) {
-                ("bot", "bot") => (
-                    Destination::Bot(words[6].parse().unwrap()),
-                    Destination::Bot(words[11].parse().unwrap()),
-                ),
-                ("bot", "output") => {
-                    let output_num = words[11].parse().unwrap();
-                    outputs.insert(output_num, Vec::new());
-                    (
-                        Destination::Bot(words[6].parse().unwrap()),
-                        Destination::Output(output_num),
-                    )
-                }
-                ("output", "bot") => {
-                    let output_num = words[6].parse().unwrap();
-                    outputs.insert(output_num, Vec::new());
-                    (
-                        Destination::Output(output_num),
-                        Destination::Bot(words[11].parse().unwrap()),
-                    )
-                }
-                ("output", "output") => {
-                    let output_num1 = words[6].parse().unwrap();
-                    let output_num2 = words[11].parse().unwrap();
-                    outputs.insert(output_num1, Vec::new());
-                    outputs.insert(output_num2, Vec::new());
-                    (
-                        Destination::Output(output_num1),
-                        Destination::Output(output_num2),
-                    )
-                }
-                _ => panic!("Invalid string {}", line),
-            };
-            bots.insert(
-                words[1].parse().unwrap(),
-                Bot {
-                    low: dest_a,
-                    high: dest_b,
-                    contents: Contents::Empty,
-                },
-            );
-        } else {
-            let words = line.split_whitespace().collect::<Vec<_>>();
-            let value = words[1].parse().unwrap();
-            let bot_no = words[5].parse().unwrap();
-            starting_values.insert(value, bot_no);
-        }
-    }
-    let mut map = Map { bots, outputs };
-    map.process(starting_values)
+enum Line {
+    Value(u32, u32),
+    Gives(u32, Destination, Destination),
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
+/// Parses the bots and the values they start with.
+fn parse(input: &str) -> Result<(Map, HashMap<u32, u32>), ParseError> {
     let mut bots = HashMap::new();
     let mut outputs = HashMap::new();
     let mut starting_values = HashMap::new();
-    for line in input.lines() {
-        if line.contains("gives") {
-            let words = line.split_whitespace().collect::<Vec<_>>();
-            let (dest_a, dest_b) = match (words[5], words[10]) {
-                ("bot", "bot") => (
-                    Destination::Bot(words[6].parse().unwrap()),
-                    Destination::Bot(words[11].parse().unwrap()),
-                ),
-                ("bot", "output") => {
-                    let output_num = words[11].parse().unwrap();
-                    outputs.insert(output_num, Vec::new());
-                    (
-                        Destination::Bot(words[6].parse().unwrap()),
-                        Destination::Output(output_num),
-                    )
-                }
-                ("output", "bot") => {
-                    let output_num = words[6].parse().unwrap();
-                    outputs.insert(output_num, Vec::new());
-                    (
-                        Destination::Output(output_num),
-                        Destination::Bot(words[11].parse().unwrap()),
-                    )
-                }
-                ("output", "output") => {
-                    let output_num1 = words[6].parse().unwrap();
-                    let output_num2 = words[11].parse().unwrap();
-                    outputs.insert(output_num1, Vec::new());
-                    outputs.insert(output_num2, Vec::new());
-                    (
-                        Destination::Output(output_num1),
-                        Destination::Output(output_num2),
+    let lines = map_lines(input, |line| {
+        if line.starts_with("value") {
+            VALUE
+                .parse(line)
+                .map(|(value, bot)| Line::Value(value, bot))
+        } else {
+            GIVES
+                .parse(line)
+                .map(|(bot, low_kind, low, high_kind, high)| {
+                    Line::Gives(
+                        bot,
+                        Destination::new(low_kind, low),
+                        Destination::new(high_kind, high),
                     )
+                })
+        }
+    });
+    for line in lines {
+        match line? {
+            Line::Value(value, bot) => {
+                starting_values.insert(value, bot);
+            }
+            Line::Gives(bot, low, high) => {
+                for dest in [low, high] {
+                    if let Destination::Output(n) = dest {
+                        outputs.insert(n, Vec::new());
+                    }
                 }
-                _ => panic!("Invalid string {}", line),
-            };
-            bots.insert(
-                words[1].parse().unwrap(),
-                Bot {
-                    low: dest_a,
-                    high: dest_b,
-                    contents: Contents::Empty,
-                },
-            );
-        } else {
-            let words = line.split_whitespace().collect::<Vec<_>>();
-            let value = words[1].parse().unwrap();
-            let bot_no = words[5].parse().unwrap();
-            starting_values.insert(value, bot_no);
+                bots.insert(
+                    bot,
+                    Bot {
+                        low,
+                        high,
+                        contents: Contents::Empty,
+                    },
+                );
+            }
         }
     }
-    let mut map = Map { bots, outputs };
-    map.processv2(starting_values)
+    Ok((Map { bots, outputs }, starting_values))
+}
+
+pub fn part_one(input: &str) -> Result<Option<u32>, ParseError> {
+    let (mut map, starting_values) = parse(input)?;
+    Ok(map.process(starting_values))
+}
+
+pub fn part_two(input: &str) -> Result<Option<u32>, ParseError> {
+    let (mut map, starting_values) = parse(input)?;
+    Ok(map.processv2(starting_values))
 }
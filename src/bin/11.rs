@@ -4,12 +4,17 @@ use std::{
     str::FromStr,
 };
 
+use advent_of_code::{
+    error::{parse_lines, ParseError},
+    search::Search,
+    visualize::{self, Text},
+};
 use bitflags::bitflags;
 
 advent_of_code::solution!(11);
 
 bitflags! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
     struct FloorContents: u32 {
         const HYDROGEN_C = 1 << 0;
         const HYDROGEN_G = 1 << 1;
@@ -51,15 +56,15 @@ bitflags! {
 }
 
 impl FromStr for FloorContents {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut out = Self::empty();
-        for s in s
+        for item in s
             .split(" a ")
             .skip(1)
             .map(|s| s.trim_end_matches(" and").trim_matches([' ', '.', ',']))
         {
-            out |= Self::single_from_str(s)?;
+            out |= Self::single_from_str(item).map_err(|e| e.within(s, item))?;
         }
         Ok(out)
     }
@@ -94,7 +99,7 @@ impl Display for Facility {
 }
 
 impl FloorContents {
-    fn single_from_str(s: &str) -> Result<Self, ()> {
+    fn single_from_str(s: &str) -> Result<Self, ParseError> {
         match s {
             "thulium generator" => Ok(Self::THULIUM_G),
             "plutonium generator" => Ok(Self::PLUTONIUM_G),
@@ -110,7 +115,7 @@ impl FloorContents {
             "promethium-compatible microchip" => Ok(Self::PROMETHIUM_C),
             "hydrogen-compatible microchip" => Ok(Self::HYDROGEN_C),
             "lithium-compatible microchip" => Ok(Self::LITHIUM_C),
-            _ => Err(()),
+            _ => Err(ParseError::expected("a known generator or microchip", s)),
         }
     }
 
@@ -147,7 +152,7 @@ impl FloorContents {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Floor {
     F1 = 0,
     F2 = 1,
@@ -155,6 +160,9 @@ enum Floor {
     F4 = 3,
 }
 
+/// The number of elements, each with a chip and a generator.
+const ELEMENTS: u32 = 9;
+
 const FLOORS: [Floor; 4] = [Floor::F1, Floor::F2, Floor::F3, Floor::F4];
 
 impl Floor {
@@ -168,7 +176,7 @@ impl Floor {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct Facility {
     floors: [FloorContents; 4],
     elevator: Floor,
@@ -217,6 +225,32 @@ impl Facility {
         }
         // dbg!(&out);
         out.retain(|f| f.is_valid());
+        out.iter_mut().for_each(|f| *f = f.canonical());
+        out
+    }
+
+    /// Elements are interchangeable, so states that only differ in which element sits where are
+    /// equivalent. This relabels the elements in order of the floors of their chip and generator.
+    fn canonical(&self) -> Self {
+        let floor_of = |bits: u32| FLOORS.into_iter().find(|&f| self[f].bits() & bits != 0);
+        let mut pairs = (0..ELEMENTS)
+            .map(|i| (floor_of(1 << (2 * i)), floor_of(1 << (2 * i + 1))))
+            .filter(|&pair| pair != (None, None))
+            .collect::<Vec<_>>();
+        pairs.sort();
+
+        let mut out = Self {
+            floors: [FloorContents::empty(); 4],
+            elevator: self.elevator,
+        };
+        for (i, (chip, generator)) in pairs.into_iter().enumerate() {
+            if let Some(chip) = chip {
+                out[chip] |= FloorContents::from_bits_retain(1 << (2 * i));
+            }
+            if let Some(generator) = generator {
+                out[generator] |= FloorContents::from_bits_retain(1 << (2 * i + 1));
+            }
+        }
         out
     }
 }
@@ -235,70 +269,43 @@ impl IndexMut<Floor> for Facility {
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let floors: [FloorContents; 4] = input
-        .lines()
-        .map(|l| l.parse().unwrap())
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap();
-    let facility = Facility {
+fn parse(input: &str) -> Result<Facility, ParseError> {
+    let floors: [FloorContents; 4] = parse_lines(input)?.try_into().map_err(|floors: Vec<_>| {
+        ParseError::new(format!("expected 4 floors, found {}", floors.len()))
+    })?;
+    Ok(Facility {
         floors,
         elevator: Floor::F1,
-    };
-    let mut next = vec![facility];
-    for i in 1.. {
-        let mut new_next = Vec::with_capacity(next.len());
-        for fac in &next {
-            let next = fac.next_possible_states();
+    })
+}
 
-            new_next.extend_from_slice(&next);
-        }
-        new_next.sort();
-        new_next.dedup();
-        for fac in &new_next {
-            if fac.has_finished() {
-                return Some(i);
-            }
+/// The fewest elevator trips that bring everything to the top floor. Visualizing replays them.
+fn min_steps(facility: Facility) -> Option<u32> {
+    let mut visualizer = visualize::start();
+    let mut search = Search::new(facility);
+    if visualizer.is_some() {
+        search = search.with_paths();
+    }
+    let outcome = search.bfs(Facility::next_possible_states, Facility::has_finished);
+    if let (Some(v), Some(path)) = (&mut visualizer, &outcome.path) {
+        for state in path {
+            v.frame(&Text::new(state));
         }
-        next = new_next;
     }
-    None
+    outcome.cost().map(|steps| steps as u32)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let floors: [FloorContents; 4] = input
-        .lines()
-        .map(|l| l.parse().unwrap())
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap();
-    let mut facility = Facility {
-        floors,
-        elevator: Floor::F1,
-    };
+pub fn part_one(input: &str) -> Result<Option<u32>, ParseError> {
+    Ok(min_steps(parse(input)?))
+}
+
+pub fn part_two(input: &str) -> Result<Option<u32>, ParseError> {
+    let mut facility = parse(input)?;
     facility.floors[0] |= FloorContents::ELERIUM_C
         | FloorContents::ELERIUM_G
         | FloorContents::DILITHIUM_C
         | FloorContents::DILITHIUM_G;
-    let mut next = vec![facility];
-    for i in 1.. {
-        let mut new_next = Vec::with_capacity(next.len());
-        for fac in &next {
-            let next = fac.next_possible_states();
-
-            new_next.extend_from_slice(&next);
-        }
-        new_next.sort();
-        new_next.dedup();
-        for fac in &new_next {
-            if fac.has_finished() {
-                return Some(i);
-            }
-        }
-        next = new_next;
-    }
-    None
+    Ok(min_steps(facility))
 }
 
 #[cfg(test)]
@@ -308,6 +315,6 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(11));
+        assert_eq!(result, Ok(Some(11)));
     }
 }
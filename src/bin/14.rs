@@ -1,74 +1,81 @@
+use std::collections::VecDeque;
+
+use advent_of_code::mining::{first_triple, quintuples, Miner};
+
 advent_of_code::solution!(14);
 
-fn contains_three_in_a_row(s: &str) -> Option<char> {
-    for ss in s.as_bytes().windows(3) {
-        if ss[0] == ss[1] && ss[1] == ss[2] {
-            return Some(ss[0] as char);
-        }
-    }
-    None
+/// How many hashes after a triple may hold the matching quintuple.
+const LOOKAHEAD: usize = 1000;
+
+/// The hashes of a salt, generated on demand. Only the window from the current candidate to
+/// the end of its lookahead is kept, and every hash is computed once.
+struct Hashes {
+    miner: Miner,
+    /// The first triple and the set of quintuples of the hashes from `start` on.
+    window: VecDeque<(Option<u8>, u16)>,
+    start: usize,
 }
 
-fn contains_five_in_a_row(s: &str) -> Option<char> {
-    for ss in s.as_bytes().windows(5) {
-        if ss[0] == ss[1] && ss[1] == ss[2] && ss[2] == ss[3] && ss[3] == ss[4] {
-            return Some(ss[0] as char);
+impl Hashes {
+    fn new(salt: &str, stretch: u32) -> Self {
+        Self {
+            miner: Miner::new(salt).stretch(stretch),
+            window: VecDeque::with_capacity(LOOKAHEAD + 1),
+            start: 0,
         }
     }
-    None
-}
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let lines: Vec<_> = input.lines().collect();
-    let mut count = 63;
-    for (i, w) in lines.windows(1001).enumerate().filter(|&(_, w)| {
-        if let Some(c) = contains_three_in_a_row(w[0]) {
-            w.iter()
-                .skip(1)
-                .any(|w| Some(c) == contains_five_in_a_row(w))
-        } else {
-            false
+    /// Makes sure the window reaches up to and including `index`.
+    fn fill(&mut self, index: usize) {
+        let end = self.start + self.window.len();
+        if index >= end {
+            // hashing in chunks keeps all cores busy.
+            let to = (index + 1).max(end + 256);
+            let hashes = self.miner.mine(end as u64, to as u64, &|_, d| {
+                Some((first_triple(d), quintuples(d)))
+            });
+            self.window.extend(hashes.into_iter().map(|(_, h)| h));
         }
-    }) {
-        println!("Hit: {} at index {i}", w[0]);
-        if count == 0 {
-            return Some(i as u32);
-        } else {
-            count -= 1;
+    }
+
+    /// Whether the hash at `index` is a key. Moves the window forward to `index`.
+    fn is_key(&mut self, index: usize) -> bool {
+        while self.start < index {
+            self.window.pop_front();
+            self.start += 1;
         }
+        self.fill(index + LOOKAHEAD);
+        self.window[0].0.is_some_and(|c| {
+            self.window
+                .range(1..=LOOKAHEAD)
+                .any(|&(_, q)| q & 1 << c != 0)
+        })
     }
-    None
+}
+
+fn index_of_64th_key(salt: &str, stretch: u32) -> Option<u32> {
+    let mut hashes = Hashes::new(salt.trim(), stretch);
+    (0..)
+        .filter(|&i| hashes.is_key(i))
+        .nth(63)
+        .map(|i| i as u32)
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    index_of_64th_key(input, 0)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let lines: Vec<_> = input.lines().collect();
-    let mut count = 63;
-    for (i, w) in lines.windows(1001).enumerate().filter(|&(_, w)| {
-        if let Some(c) = contains_three_in_a_row(w[0]) {
-            w.iter()
-                .skip(1)
-                .any(|w| Some(c) == contains_five_in_a_row(w))
-        } else {
-            false
-        }
-    }) {
-        println!("Hit: {} at index {i}", w[0]);
-        if count == 0 {
-            return Some(i as u32);
-        } else {
-            count -= 1;
-        }
+    index_of_64th_key(input, 2016)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, Some(22728));
     }
-    None
 }
-//
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//
-//     #[test]
-//     fn test_part_one() {
-//         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-//         assert_eq!(result, Some(22728));
-//     }
-// }
@@ -1,5 +1,9 @@
+use advent_of_code::parse::{ParseError, Pattern};
+
 advent_of_code::solution!(15);
 
+const DISC: Pattern = Pattern::new("Disc #{} has {} positions; at time=0, it is at position {}.");
+
 fn egcd(a: i64, b: i64) -> (i64, i64, i64) {
     if a == 0 {
         (b, 0, 1)
@@ -31,38 +35,28 @@ fn chinese_remainder(residues: &[i64], modulii: &[i64]) -> Option<i64> {
     Some(prod - (sum % prod))
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
+/// Returns the residue every disc needs the capsule to be dropped at, and its modulus.
+fn parse(input: &str) -> Result<(Vec<i64>, Vec<i64>), ParseError> {
     let mut residues = Vec::new();
     let mut modulii = Vec::new();
-    for (i, line) in input.lines().enumerate() {
-        let parts = line.split_whitespace().collect::<Vec<_>>();
-        let p = parts[3].parse().unwrap();
-        let r: i64 = parts[11].trim_end_matches('.').parse().unwrap();
-        let r = (r + i as i64 + 1) % p;
-        residues.push(r);
+    for (disc, p, r) in DISC.parse_lines::<(i64, i64, i64)>(input)? {
+        residues.push((r + disc) % p);
         modulii.push(p);
     }
-    chinese_remainder(&residues, &modulii).map(|n| n as u32)
+    Ok((residues, modulii))
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut residues = Vec::new();
-    let mut modulii = Vec::new();
-    let mut last_i = 0;
-    for (i, line) in input.lines().enumerate() {
-        let parts = line.split_whitespace().collect::<Vec<_>>();
-        let p = parts[3].parse().unwrap();
-        let r: i64 = parts[11].trim_end_matches('.').parse().unwrap();
-        let r = (r + i as i64 + 1) % p;
-        residues.push(r);
-        modulii.push(p);
-        last_i = i;
-    }
-    residues.push(last_i as i64 + 2);
-    modulii.push(11);
-    chinese_remainder(&residues, &modulii).map(|n| n as u32)
+pub fn part_one(input: &str) -> Result<Option<u32>, ParseError> {
+    let (residues, modulii) = parse(input)?;
+    Ok(chinese_remainder(&residues, &modulii).map(|n| n as u32))
 }
 
+pub fn part_two(input: &str) -> Result<Option<u32>, ParseError> {
+    let (mut residues, mut modulii) = parse(input)?;
+    residues.push(residues.len() as i64 + 1);
+    modulii.push(11);
+    Ok(chinese_remainder(&residues, &modulii).map(|n| n as u32))
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,6 +64,6 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(5));
+        assert_eq!(result, Ok(Some(5)));
     }
 }
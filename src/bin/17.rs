@@ -1,4 +1,4 @@
-use md5::{digest::generic_array::GenericArray, Digest, Md5};
+use advent_of_code::{mining, search::Search};
 
 use std::{fmt::Display, str::FromStr};
 
@@ -67,128 +67,70 @@ impl FromStr for Path {
     }
 }
 
-impl Path {
-    fn loc(&self) -> (u8, u8) {
-        let mut x = 0;
-        let mut y = 0;
-        for dir in &self.0 {
-            match dir {
-                Dir::L => x -= 1,
-                Dir::R => x += 1,
-                Dir::D => y += 1,
-                Dir::U => y -= 1,
-            }
-        }
-        (x, y)
-    }
+const DIRS: [Dir; 4] = [Dir::U, Dir::D, Dir::L, Dir::R];
+
+/// A position in the vault together with the path that led there.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct State {
+    path: String,
+    loc: (u8, u8),
 }
 
-const DIRS: [Dir; 4] = [Dir::L, Dir::R, Dir::U, Dir::D];
+const VAULT: (u8, u8) = (3, 3);
 
-pub fn part_one(input: &str) -> Option<Path> {
-    let input = input.trim().as_bytes();
-    let mut attempts: Vec<Path> = Vec::new();
-    let mut winner: Option<Path> = None;
-    let mut md5 = Md5::new();
-    let mut output = GenericArray::default();
-    md5.update(input);
-    md5.finalize_into_reset(&mut output);
-    let (x, y) = (0, 0);
-    for dir in DIRS.iter().filter_map(|dir| match (dir, x, y) {
-        (Dir::L, 0, _) | (Dir::R, 3, _) | (Dir::U, _, 0) | (Dir::D, _, 3) => None,
-        (Dir::L, _, _) if (output[1] >> 4) > 10 => Some(Dir::L),
-        (Dir::R, _, _) if (output[1] & 0b00001111) > 10 => Some(Dir::R),
-        (Dir::U, _, _) if (output[0] >> 4) > 10 => Some(Dir::U),
-        (Dir::D, _, _) if (output[0] & 0b00001111) > 10 => Some(Dir::D),
-        _ => None,
-    }) {
-        let mut path = Path(Vec::new());
-        path.0.push(dir);
-        attempts.push(path);
+/// The rooms whose doors are open, judging by the hash of the passcode and the path so far.
+/// Nothing leads out of the vault.
+fn neighbours(passcode: &str, state: &State) -> Vec<State> {
+    if state.loc == VAULT {
+        return vec![];
     }
-    while winner.is_none() {
-        let mut new_attempts: Vec<Path> = Vec::new();
-        for path in &attempts {
-            md5.update(input);
-            md5.update(path.to_string().as_bytes());
-            md5.finalize_into_reset(&mut output);
-            let (x, y) = path.loc();
-            for (dir, x, y) in DIRS.iter().filter_map(|dir| match (dir, x, y) {
-                (Dir::L, 0, _) | (Dir::R, 3, _) | (Dir::U, _, 0) | (Dir::D, _, 3) => None,
-                (Dir::L, x, y) if (output[1] >> 4) > 10 => Some((Dir::L, x - 1, y)),
-                (Dir::R, x, y) if (output[1] & 0b00001111) > 10 => Some((Dir::R, x + 1, y)),
-                (Dir::U, x, y) if (output[0] >> 4) > 10 => Some((Dir::U, x, y - 1)),
-                (Dir::D, x, y) if (output[0] & 0b00001111) > 10 => Some((Dir::D, x, y + 1)),
-                _ => None,
-            }) {
-                let mut path = path.clone();
-                path.0.push(dir);
-                if x == 3 && y == 3 {
-                    winner = Some(path);
-                    break;
-                } else {
-                    new_attempts.push(path);
-                }
-            }
-        }
-        attempts = new_attempts;
+    let digest = mining::digest(format!("{passcode}{}", state.path).as_bytes());
+
+    let (x, y) = state.loc;
+    DIRS.iter()
+        .enumerate()
+        .filter(|&(i, _)| mining::nibble(&digest, i) > 10)
+        .filter_map(|(_, &dir)| {
+            let loc = match dir {
+                Dir::U if y > 0 => (x, y - 1),
+                Dir::D if y < 3 => (x, y + 1),
+                Dir::L if x > 0 => (x - 1, y),
+                Dir::R if x < 3 => (x + 1, y),
+                _ => return None,
+            };
+            Some(State {
+                path: format!("{}{dir}", state.path),
+                loc,
+            })
+        })
+        .collect()
+}
+
+fn start() -> State {
+    State {
+        path: String::new(),
+        loc: (0, 0),
     }
-    winner
+}
+
+pub fn part_one(input: &str) -> Option<Path> {
+    let passcode = input.trim();
+    let (vault, _) = Search::new(start())
+        .bfs(|s| neighbours(passcode, s), |s| s.loc == VAULT)
+        .found?;
+    vault.path.parse().ok()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let input = input.trim().as_bytes();
-    let mut attempts: Vec<Path> = Vec::new();
-    let mut md5 = Md5::new();
-    let mut output = GenericArray::default();
-    md5.update(input);
-    md5.finalize_into_reset(&mut output);
-    let (x, y) = (0, 0);
-    for dir in DIRS.iter().filter_map(|dir| match (dir, x, y) {
-        (Dir::L, 0, _) | (Dir::R, 3, _) | (Dir::U, _, 0) | (Dir::D, _, 3) => None,
-        (Dir::L, _, _) if (output[1] >> 4) > 10 => Some(Dir::L),
-        (Dir::R, _, _) if (output[1] & 0b00001111) > 10 => Some(Dir::R),
-        (Dir::U, _, _) if (output[0] >> 4) > 10 => Some(Dir::U),
-        (Dir::D, _, _) if (output[0] & 0b00001111) > 10 => Some(Dir::D),
-        _ => None,
-    }) {
-        let mut path = Path(Vec::new());
-        path.0.push(dir);
-        attempts.push(path);
-    }
-    let mut last_final = None;
-    loop {
-        let mut new_attempts: Vec<Path> = Vec::new();
-        for path in &attempts {
-            md5.update(input);
-            md5.update(path.to_string().as_bytes());
-            md5.finalize_into_reset(&mut output);
-            let (x, y) = path.loc();
-            for (dir, x, y) in DIRS.iter().filter_map(|dir| match (dir, x, y) {
-                (Dir::L, 0, _) | (Dir::R, 3, _) | (Dir::U, _, 0) | (Dir::D, _, 3) => None,
-                (Dir::L, x, y) if (output[1] >> 4) > 10 => Some((Dir::L, x - 1, y)),
-                (Dir::R, x, y) if (output[1] & 0b00001111) > 10 => Some((Dir::R, x + 1, y)),
-                (Dir::U, x, y) if (output[0] >> 4) > 10 => Some((Dir::U, x, y - 1)),
-                (Dir::D, x, y) if (output[0] & 0b00001111) > 10 => Some((Dir::D, x, y + 1)),
-                _ => None,
-            }) {
-                let mut path = path.clone();
-                path.0.push(dir);
-                if x == 3 && y == 3 {
-                    last_final = Some(path);
-                    continue;
-                } else {
-                    new_attempts.push(path);
-                }
-            }
-        }
-        if let Some(ref p) = last_final {
-            if new_attempts.is_empty() {
-                return Some(p.0.len() as u32);
-            }
-        }
-        attempts = new_attempts;
-    }
+    let passcode = input.trim();
+    // every path is a different state, so the search is exhaustive.
+    let reached = Search::new(start()).reachable(|s| neighbours(passcode, s), usize::MAX);
+    reached
+        .depths
+        .into_iter()
+        .filter(|(s, _)| s.loc == VAULT)
+        .map(|(_, depth)| depth as u32)
+        .max()
 }
 
 #[cfg(test)]
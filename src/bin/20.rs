@@ -1,7 +1,11 @@
 use std::{fmt::Display, str::FromStr};
 
+use advent_of_code::parse::{parse_lines, ParseError, Pattern};
+
 advent_of_code::solution!(20);
 
+const RANGE: Pattern = Pattern::new("{}-{}");
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 struct Range {
     begin: u32,
@@ -9,23 +13,10 @@ struct Range {
 }
 
 impl FromStr for Range {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (begin, end) = s
-            .split_once('-')
-            .ok_or(format!("{s} doesn't contain '-'"))
-            .and_then(|(first, second)| {
-                first
-                    .parse()
-                    .map_err(|e| format!("Invalid u32: {e:?}"))
-                    .and_then(|first| {
-                        second
-                            .parse()
-                            .map_err(|e| format!("Invalid u32: {e:?}"))
-                            .map(|second| (first, second))
-                    })
-            })?;
+        let (begin, end) = RANGE.parse(s)?;
         Ok(Self { begin, end })
     }
 }
@@ -36,11 +27,8 @@ impl Display for Range {
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut ranges = input
-        .lines()
-        .map(|l| l.parse::<Range>().unwrap())
-        .collect::<Vec<_>>();
+pub fn part_one(input: &str) -> Result<Option<u32>, ParseError> {
+    let mut ranges = parse_lines::<Range>(input)?;
     ranges.sort_by_key(|r| r.begin);
     for range in &mut ranges {
         range.end = range.end.saturating_add(1);
@@ -55,14 +43,11 @@ pub fn part_one(input: &str) -> Option<u32> {
         }
     }
     ranges.truncate(index + 1);
-    ranges.first().map(|r| r.end)
+    Ok(ranges.first().map(|r| r.end))
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut ranges = input
-        .lines()
-        .map(|l| l.parse::<Range>().unwrap())
-        .collect::<Vec<_>>();
+pub fn part_two(input: &str) -> Result<u32, ParseError> {
+    let mut ranges = parse_lines::<Range>(input)?;
     ranges.sort_by_key(|r| r.begin);
     for range in &mut ranges {
         range.end = range.end.saturating_add(1);
@@ -77,5 +62,5 @@ pub fn part_two(input: &str) -> Option<u32> {
         }
     }
     ranges.truncate(index + 1);
-    Some(ranges.windows(2).map(|w| w[1].begin - w[0].end).sum())
+    Ok(ranges.windows(2).map(|w| w[1].begin - w[0].end).sum())
 }
@@ -1,7 +1,17 @@
 use std::{fmt::Display, str::FromStr};
 
+use advent_of_code::parse::{parse_lines, ParseError, Pattern};
+
 advent_of_code::solution!(21);
 
+const SWAP_POSITION: Pattern = Pattern::new("swap position {} with position {}");
+const SWAP_LETTER: Pattern = Pattern::new("swap letter {} with letter {}");
+const ROTATE_LEFT: Pattern = Pattern::new("rotate left {} step");
+const ROTATE_RIGHT: Pattern = Pattern::new("rotate right {} step");
+const ROTATE_LETTER: Pattern = Pattern::new("rotate based on position of letter {}");
+const REVERSE: Pattern = Pattern::new("reverse positions {} through {}");
+const MOVE: Pattern = Pattern::new("move position {} to position {}");
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Password(Vec<char>);
 
@@ -48,51 +58,27 @@ impl Command {
 }
 
 impl FromStr for Command {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut words = s.split_whitespace();
-        let err = || format!("Not parsable {s}");
-        match words.next().unwrap() {
-            "rotate" => match words.next().unwrap() {
-                "left" => {
-                    let steps = words.next().unwrap().parse().map_err(|_| err())?;
-                    Ok(Self::RotateL(steps))
-                }
-                "right" => {
-                    let steps = words.next().unwrap().parse().map_err(|_| err())?;
-                    Ok(Self::RotateR(steps))
-                }
-                "based" => {
-                    let c = words.nth(4).unwrap().chars().next().unwrap();
-                    Ok(Self::RotateC(c))
-                }
-                _ => Err(err()),
-            },
-            "swap" => match words.next().unwrap() {
-                "letter" => {
-                    let a = words.next().unwrap().chars().next().unwrap();
-                    let b = words.nth(2).unwrap().chars().next().unwrap();
-                    Ok(Self::SwapC(a, b))
-                }
-                "position" => {
-                    let x = words.next().unwrap().parse().unwrap();
-                    let y = words.nth(2).unwrap().parse().unwrap();
-                    Ok(Self::Swap(x, y))
-                }
-                _ => Err(err()),
-            },
-            "reverse" => {
-                let x = words.nth(1).unwrap().parse().unwrap();
-                let y = words.nth(1).unwrap().parse().unwrap();
-                Ok(Self::Reverse(x, y))
+        // `step` and `steps` read the same.
+        let steps = s.strip_suffix('s').unwrap_or(s);
+        match (words.next(), words.next()) {
+            (Some("swap"), Some("position")) => {
+                SWAP_POSITION.parse(s).map(|(x, y)| Self::Swap(x, y))
             }
-            "move" => {
-                let x = words.nth(1).unwrap().parse().unwrap();
-                let y = words.nth(2).unwrap().parse().unwrap();
-                Ok(Self::Move(x, y))
+            (Some("swap"), Some("letter")) => SWAP_LETTER.parse(s).map(|(a, b)| Self::SwapC(a, b)),
+            (Some("rotate"), Some("left")) => ROTATE_LEFT.parse(steps).map(|(n,)| Self::RotateL(n)),
+            (Some("rotate"), Some("right")) => {
+                ROTATE_RIGHT.parse(steps).map(|(n,)| Self::RotateR(n))
             }
-            _ => Err(err()),
+            (Some("rotate"), Some("based")) => ROTATE_LETTER.parse(s).map(|(c,)| Self::RotateC(c)),
+            (Some("reverse"), _) => REVERSE.parse(s).map(|(x, y)| Self::Reverse(x, y)),
+            (Some("move"), _) => MOVE.parse(s).map(|(x, y)| Self::Move(x, y)),
+            _ => Err(
+                ParseError::expected("a swap, rotate, reverse or move operation", s).within(s, s),
+            ),
         }
     }
 }
@@ -177,20 +163,16 @@ impl Password {
     }
 }
 
-pub fn part_one(input: &str) -> Option<Password> {
-    let commands = input.lines().map(|l| l.parse().unwrap());
+pub fn part_one(input: &str) -> Result<String, ParseError> {
+    let commands = parse_lines::<Command>(input)?;
     let mut pass = Password::from("abcdefgh");
     pass.compute(commands);
-    Some(pass)
+    Ok(pass.to_string())
 }
 
-pub fn part_two(input: &str) -> Option<Password> {
-    let mut commands = input
-        .lines()
-        .map(|l| l.parse::<Command>().unwrap().reverse())
-        .collect::<Vec<_>>();
-    commands.reverse();
+pub fn part_two(input: &str) -> Result<String, ParseError> {
+    let commands = parse_lines::<Command>(input)?;
     let mut pass = Password::from("fbgdceah");
-    pass.compute(commands);
-    Some(pass)
+    pass.compute(commands.into_iter().rev().map(Command::reverse));
+    Ok(pass.to_string())
 }
@@ -1,7 +1,18 @@
-use std::{fmt::Display, str::FromStr};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    str::FromStr,
+};
+
+use advent_of_code::{
+    grid::Grid,
+    parse::{ParseError, Pattern},
+};
 
 advent_of_code::solution!(22);
 
+const NODE: Pattern = Pattern::new("/dev/grid/node-x{}-y{} {}T {}T {}T {}%");
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Node {
     x: u32,
@@ -11,28 +22,10 @@ struct Node {
 }
 
 impl FromStr for Node {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.strip_prefix("/dev/grid/node-").unwrap();
-        let mut parts = s.split_whitespace();
-        let xy = parts.next().unwrap();
-        let (x, y) = xy
-            .split_once('-')
-            .map(|(x, y)| {
-                (
-                    x.strip_prefix('x').unwrap().parse().unwrap(),
-                    y.strip_prefix('y').unwrap().parse().unwrap(),
-                )
-            })
-            .unwrap();
-        let used = parts.nth(1).unwrap();
-        let used = used.strip_suffix('T').unwrap();
-        let used = used.parse().unwrap();
-        let avail = parts.next().unwrap();
-        let avail = avail.strip_suffix('T').unwrap();
-        let avail = avail.parse().unwrap();
-
+        let [x, y, _size, used, avail, _use] = NODE.parse(s)?;
         Ok(Self { x, y, used, avail })
     }
 }
@@ -47,26 +40,92 @@ impl Display for Node {
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
+fn parse_grid(input: &str) -> Result<Grid<Node>, ParseError> {
+    // the first two lines are the `df` command and its header.
     let nodes = input
         .lines()
+        .enumerate()
         .skip(2)
-        .map(|l| l.parse::<Node>().unwrap())
-        .collect::<Vec<_>>();
+        .map(|(i, l)| {
+            l.parse::<Node>()
+                .map(|n| ((n.x as usize, n.y as usize), n))
+                .map_err(|e| e.at_line(i + 1, l))
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    let width = nodes.keys().map(|p| p.0 + 1).max().unwrap_or(0);
+    let height = nodes.keys().map(|p| p.1 + 1).max().unwrap_or(0);
+    if nodes.is_empty() || nodes.len() != width * height {
+        return Err(ParseError::new(format!(
+            "expected the nodes of a full {width}x{height} grid, found {}",
+            nodes.len()
+        )));
+    }
+    Ok(Grid::from_fn(width, height, |p| nodes[&p]))
+}
+
+pub fn part_one(input: &str) -> Result<u32, ParseError> {
+    let grid = parse_grid(input)?;
     let mut total = 0;
-    for node in &nodes {
-        total += if node.used != 0 {
-            nodes
+    for (p, node) in grid.iter() {
+        if node.used != 0 {
+            total += grid
                 .iter()
-                .filter(|n| (n.x != node.x || n.y != node.y) && n.avail >= node.used)
-                .count()
-        } else {
-            0
-        };
+                .filter(|&(q, n)| q != p && n.avail >= node.used)
+                .count();
+        }
+    }
+    Ok(total as u32)
+}
+
+/// Nodes can only ever pass their data to the empty node, so the empty node walks to the goal
+/// data around the nodes too full to fit into it, then shuffles it left to the origin. Every
+/// step left takes five moves: one to swap, four to bring the empty node around again.
+fn fewest_moves(grid: &Grid<Node>) -> Option<u32> {
+    let (empty, size) = grid
+        .iter()
+        .find(|(_, n)| n.used == 0)
+        .map(|(p, n)| (p, n.avail))?;
+    let goal = (grid.width() - 1, 0);
+    if goal.0 == 0 {
+        return Some(0);
     }
-    Some(total as u32)
+
+    let target = (goal.0 - 1, 0);
+    let mut distances = HashMap::from([(empty, 0)]);
+    let mut queue = VecDeque::from([empty]);
+    while let Some(p) = queue.pop_front() {
+        if p == target {
+            break;
+        }
+        let d = distances[&p];
+        for q in grid.neighbours4(p) {
+            if q != goal && grid[q].used <= size && !distances.contains_key(&q) {
+                distances.insert(q, d + 1);
+                queue.push_back(q);
+            }
+        }
+    }
+
+    Some(distances.get(&target)? + 1 + 5 * target.0 as u32)
+}
+
+pub fn part_two(input: &str) -> Result<Option<u32>, ParseError> {
+    Ok(fewest_moves(&parse_grid(input)?))
 }
 
-pub fn part_two(_input: &str) -> Option<u32> {
-    Some(242)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, Ok(7));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, Ok(Some(7)));
+    }
 }
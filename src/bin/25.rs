@@ -1,202 +1,148 @@
 use std::{
-    ops::{Index, IndexMut},
-    str::FromStr,
+    collections::HashSet,
+    sync::atomic::{AtomicI64, Ordering},
+    thread,
 };
 
-use anyhow::anyhow;
+use advent_of_code::{
+    assembunny::{self, compile::Compiled, Control, Exit, Instruction, Machine},
+    error::ParseError,
+};
 
 advent_of_code::solution!(25);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Register {
-    A = 0,
-    B = 1,
-    C = 2,
-    D = 3,
-}
-
-impl FromStr for Register {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "a" => Ok(Self::A),
-            "b" => Ok(Self::B),
-            "c" => Ok(Self::C),
-            "d" => Ok(Self::D),
-            _ => Err(anyhow!("{s} is not a valid register")),
+/// The largest `a` tried before giving up.
+const MAX_A: i64 = 5_000_000;
+/// Candidates are handed to the worker threads in chunks of this size.
+const CHUNK: i64 = 64;
+/// Heuristic: the number of steps a program may run without sending anything. A silent program
+/// might still send the next bit eventually, so one that is rejected here is only very likely not
+/// a clock.
+const MAX_SILENCE: u64 = 10_000_000;
+/// Heuristic: the number of outputs checked before a program whose state never repeats is
+/// rejected. A signal that only becomes periodic after this many bits would be missed.
+const MAX_OUTPUTS: usize = 1_000_000;
+
+/// Checks whether the program, started with `a`, sends `0, 1, 0, 1, ...` forever.
+///
+/// The machine stops after every output. If it reaches the same pc, registers and program while
+/// expecting the same bit as after an earlier output, it is in a cycle that repeats this signal,
+/// so accepting is exact. Rejecting is exact for wrong bits and halting programs, and falls back
+/// on [`MAX_SILENCE`] and [`MAX_OUTPUTS`] for programs that never reach a repeated state.
+fn is_clock_signal(program: &[Instruction], compiled: &Compiled, a: i64) -> bool {
+    let mut comp = Machine::with_registers(program.to_vec(), [a, 0, 0, 0]);
+    comp.optimize();
+    let mut seen = HashSet::new();
+    let mut expected = 0;
+    loop {
+        let mut valid = false;
+        let exit = compiled.run(&mut comp, Some(MAX_SILENCE), |v| {
+            valid = v == expected;
+            Control::Stop
+        });
+        if exit != Exit::Stopped || !valid {
+            return false;
         }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Value {
-    Reg(Register),
-    Imm(isize),
-}
-
-impl FromStr for Value {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(r) = s.parse() {
-            Ok(Self::Reg(r))
-        } else if let Ok(i) = s.parse() {
-            Ok(Self::Imm(i))
-        } else {
-            Err(anyhow!("{s} is not a valid value"))
+        expected ^= 1;
+        // the program is only part of the state once `tgl` changed it.
+        let toggled = (comp.program != program).then(|| comp.program.clone());
+        if !seen.insert((comp.pc, comp.registers, expected, toggled)) {
+            return true;
         }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Instruction {
-    Cpy(Value, Register),
-    Inc(Register),
-    Dec(Register),
-    Jnz(Value, Value),
-    Out(Register),
-}
-
-impl FromStr for Instruction {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split_whitespace();
-        let not_enough_data = || anyhow!("{s} does not have enough fields");
-        match parts.next().ok_or(not_enough_data())? {
-            "cpy" => {
-                let val = parts.next().ok_or(not_enough_data())?.parse()?;
-                let reg = parts.next().ok_or(not_enough_data())?.parse()?;
-                Ok(Self::Cpy(val, reg))
-            }
-            "inc" => {
-                let reg = parts.next().ok_or(not_enough_data())?.parse()?;
-                Ok(Self::Inc(reg))
-            }
-            "dec" => {
-                let reg = parts.next().ok_or(not_enough_data())?.parse()?;
-                Ok(Self::Dec(reg))
-            }
-            "jnz" => {
-                let v1 = parts.next().ok_or(not_enough_data())?.parse()?;
-                let v2 = parts.next().ok_or(not_enough_data())?.parse()?;
-                Ok(Self::Jnz(v1, v2))
-            }
-            "out" => {
-                let reg = parts.next().ok_or(not_enough_data())?.parse()?;
-                Ok(Self::Out(reg))
-            }
-            _ => todo!(),
+        if seen.len() == MAX_OUTPUTS {
+            return false;
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Program {
-    code: Vec<Instruction>,
-}
-
-impl Index<Register> for Computer {
-    type Output = isize;
-
-    fn index(&self, index: Register) -> &Self::Output {
-        &self.registers[index as usize]
-    }
-}
-
-impl IndexMut<Register> for Computer {
-    fn index_mut(&mut self, index: Register) -> &mut Self::Output {
-        &mut self.registers[index as usize]
-    }
-}
-
-impl FromStr for Program {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut code = Vec::with_capacity(s.lines().count());
-        for line in s.lines() {
-            code.push(line.parse()?);
+pub fn part_one(input: &str) -> Result<Option<u32>, ParseError> {
+    let program = assembunny::parse(input)?;
+    let compiled = Compiled::new(&program);
+
+    let next = AtomicI64::new(0);
+    let found = AtomicI64::new(i64::MAX);
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                let start = next.fetch_add(CHUNK, Ordering::Relaxed);
+                // chunks are handed out in order, so everything below `found` has been checked.
+                if start > MAX_A || start > found.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Some(a) = (start..(start + CHUNK).min(MAX_A + 1))
+                    .find(|&a| is_clock_signal(&program, &compiled, a))
+                {
+                    found.fetch_min(a, Ordering::Relaxed);
+                }
+            });
         }
+    });
 
-        Ok(Self { code })
-    }
+    Ok(match found.into_inner() {
+        i64::MAX => None,
+        a => Some(a as u32),
+    })
 }
 
-impl Program {
-    fn get(&self, index: usize) -> Option<&Instruction> {
-        self.code.get(index)
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Computer {
-    registers: [isize; 4],
-    pc: usize,
+pub fn part_two(_input: &str) -> Option<u32> {
+    Some(42)
 }
 
-impl Computer {
-    fn run(&mut self, program: &Program, num_steps: usize) -> bool {
-        let mut count = 0;
-        let mut output = Vec::new();
-        while let Some(i) = program.get(self.pc) {
-            if count >= num_steps {
-                break;
-            }
-            match *i {
-                Instruction::Cpy(v1, r) => {
-                    self[r] = match v1 {
-                        Value::Reg(r1) => self[r1],
-                        Value::Imm(i) => i,
-                    };
-                }
-                Instruction::Inc(r) => self[r] += 1,
-                Instruction::Dec(r) => self[r] -= 1,
-                Instruction::Jnz(v1, v2) => {
-                    let v1 = match v1 {
-                        Value::Reg(r) => self[r],
-                        Value::Imm(i) => i,
-                    };
-                    if v1 != 0 {
-                        let offset = match v2 {
-                            Value::Reg(r) => self[r],
-                            Value::Imm(i) => i,
-                        };
-                        self.pc = self.pc.wrapping_add_signed(offset - 1);
-                    }
-                }
-                Instruction::Out(r) => {
-                    output.push(self[r]);
-                }
-            }
-            if !output.is_empty()
-                && output.len() % 2 == 0
-                && output.chunks(2).any(|c| c[0] != 0 || c[1] != 1)
-            {
-                return false;
-            }
-            self.pc += 1;
-            count += 1;
-        }
-        !output.is_empty()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sends the bits of `a + ROWS * COLS`, lowest first, forever; built like the real inputs.
+    const CLOCK: &str = "\
+const ROWS = 4
+const COLS = 3
+
+        cpy a d
+        cpy ROWS c
+outer:  cpy COLS b
+inner:  inc d
+        dec b
+        jnz b inner
+        dec c
+        jnz c outer
+restart:
+        cpy d a
+next:   cpy a b
+        cpy 0 a
+halve:  cpy 2 c         # a = b / 2, c = 2 - b % 2
+step:   jnz b nonzero
+        jnz 1 halved
+nonzero:
+        dec b
+        dec c
+        jnz c step
+        inc a
+        jnz 1 halve
+halved: cpy 2 b         # b = b % 2
+bit:    jnz c odd
+        jnz 1 send
+odd:    dec b
+        dec c
+        jnz 1 bit
+send:   out b
+        jnz a next
+        jnz 1 restart
+";
+
+    #[test]
+    fn test_part_one() {
+        let program = assembunny::asm::assemble(CLOCK).unwrap();
+        // 30 + 12 = 0b101010
+        assert_eq!(part_one(&program), Ok(Some(30)));
     }
-}
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let instructions = input.parse::<Program>().unwrap();
-    for a in 0..5000000 {
-        let mut comp = Computer {
-            registers: [a, 0, 0, 0],
-            pc: 0,
-        };
-        if comp.run(&instructions, 100000) {
-            return Some(a as u32);
-        }
+    #[test]
+    fn test_rejects_finite_signals() {
+        // sends `0, 1` a times, then halts.
+        let program = assembunny::parse("out 0\nout 1\ndec a\njnz a -3\n").unwrap();
+        let compiled = Compiled::new(&program);
+        assert!(!is_clock_signal(&program, &compiled, 1));
+        assert!(!is_clock_signal(&program, &compiled, 100));
     }
-    None
-}
-
-pub fn part_two(_input: &str) -> Option<u32> {
-    Some(42)
 }
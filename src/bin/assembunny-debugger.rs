@@ -0,0 +1,524 @@
+//! Interactive debugger for the assembunny programs of days 12, 23 and 25.
+//!
+//! Usage: `cargo bunny-debug <program> [-a <n>] [-b <n>] [-c <n>] [-d <n>]`, then type `help`.
+
+use std::{
+    fmt::Display,
+    fs,
+    io::{stdin, stdout, BufRead, Write},
+    process,
+    str::FromStr,
+};
+
+use advent_of_code::assembunny::{self, Control, Instruction, Machine, Register};
+use advent_of_code::template::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+/// Steps after which `continue` pauses when nothing else stopped it.
+const MAX_CONTINUE_STEPS: u64 = 100_000_000;
+
+const HELP: &str = "\
+Commands:
+  s, step [n]             execute n instructions (default 1)
+  c, continue [n]         run until a breakpoint, a watchpoint or the end (at most n steps)
+  b, break <pc>           break before executing the instruction at pc
+  b, break if <r> <op> <n>  break when a register comparison holds (op: == != < <= > >=)
+  w, watch <r>            break when a register changes
+  d, delete <id>          delete a breakpoint or watchpoint
+  i, info                 list breakpoints and watchpoints
+  r, regs                 show registers
+  set <r> <n>             set a register
+  l, list                 show the program, marking the pc, breakpoints and toggled instructions
+  p, profile              show how often each instruction was executed
+  o, output               show everything sent by `out`
+  reset                   restart with the initial registers and program
+  h, help                 show this help
+  q, quit                 exit
+An empty line repeats the last command.";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    fn holds(&self, lhs: i64, rhs: i64) -> bool {
+        match self {
+            Cmp::Eq => lhs == rhs,
+            Cmp::Ne => lhs != rhs,
+            Cmp::Lt => lhs < rhs,
+            Cmp::Le => lhs <= rhs,
+            Cmp::Gt => lhs > rhs,
+            Cmp::Ge => lhs >= rhs,
+        }
+    }
+}
+
+impl FromStr for Cmp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "==" => Ok(Self::Eq),
+            "!=" => Ok(Self::Ne),
+            "<" => Ok(Self::Lt),
+            "<=" => Ok(Self::Le),
+            ">" => Ok(Self::Gt),
+            ">=" => Ok(Self::Ge),
+            _ => Err(format!("`{s}` is not a valid comparison")),
+        }
+    }
+}
+
+impl Display for Cmp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Cmp::Eq => "==",
+            Cmp::Ne => "!=",
+            Cmp::Lt => "<",
+            Cmp::Le => "<=",
+            Cmp::Gt => ">",
+            Cmp::Ge => ">=",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Breakpoint {
+    Pc(usize),
+    Condition(Register, Cmp, i64),
+    Watch(Register),
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Breakpoint::Pc(pc) => write!(f, "break at pc {pc}"),
+            Breakpoint::Condition(r, cmp, n) => write!(f, "break if {r} {cmp} {n}"),
+            Breakpoint::Watch(r) => write!(f, "watch {r}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Command {
+    Step(u64),
+    Continue(Option<u64>),
+    Break(Breakpoint),
+    Delete(usize),
+    Info,
+    Registers,
+    Set(Register, i64),
+    List,
+    Profile,
+    Output,
+    Reset,
+    Help,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let number = |s: &str| {
+            s.parse::<i64>()
+                .map_err(|_| format!("`{s}` is not a number"))
+        };
+        let count = |s: &str| {
+            s.parse::<u64>()
+                .map_err(|_| format!("`{s}` is not a valid count"))
+        };
+
+        match parts.as_slice() {
+            ["s" | "step"] => Ok(Self::Step(1)),
+            ["s" | "step", n] => Ok(Self::Step(count(n)?)),
+            ["c" | "continue"] => Ok(Self::Continue(None)),
+            ["c" | "continue", n] => Ok(Self::Continue(Some(count(n)?))),
+            ["b" | "break", "if", r, cmp, n] => Ok(Self::Break(Breakpoint::Condition(
+                r.parse()?,
+                cmp.parse()?,
+                number(n)?,
+            ))),
+            ["b" | "break", pc] => Ok(Self::Break(Breakpoint::Pc(
+                pc.parse()
+                    .map_err(|_| format!("`{pc}` is not a valid pc"))?,
+            ))),
+            ["w" | "watch", r] => Ok(Self::Break(Breakpoint::Watch(r.parse()?))),
+            ["d" | "delete", id] => Ok(Self::Delete(
+                id.parse()
+                    .map_err(|_| format!("`{id}` is not a valid id"))?,
+            )),
+            ["i" | "info"] => Ok(Self::Info),
+            ["r" | "regs"] => Ok(Self::Registers),
+            ["set", r, n] => Ok(Self::Set(r.parse()?, number(n)?)),
+            ["l" | "list"] => Ok(Self::List),
+            ["p" | "profile"] => Ok(Self::Profile),
+            ["o" | "output"] => Ok(Self::Output),
+            ["reset"] => Ok(Self::Reset),
+            ["h" | "help"] => Ok(Self::Help),
+            ["q" | "quit"] => Ok(Self::Quit),
+            _ => Err(format!("unknown command `{s}`, type `help` for a list")),
+        }
+    }
+}
+
+/// Why `step` or `continue` stopped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Stop {
+    Halted,
+    Steps,
+    Breakpoint(usize),
+    Watchpoint(usize, i64, i64),
+}
+
+struct Debugger {
+    machine: Machine,
+    program: Vec<Instruction>,
+    registers: [i64; 4],
+    /// Breakpoints by id; deleted ones are kept as `None` so ids stay stable.
+    breakpoints: Vec<Option<Breakpoint>>,
+    /// Number of times each instruction was executed.
+    profile: Vec<u64>,
+    output: Vec<i64>,
+}
+
+impl Debugger {
+    fn new(program: Vec<Instruction>, registers: [i64; 4]) -> Self {
+        Self {
+            machine: Machine::with_registers(program.clone(), registers),
+            profile: vec![0; program.len()],
+            program,
+            registers,
+            breakpoints: vec![],
+            output: vec![],
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self {
+            breakpoints: std::mem::take(&mut self.breakpoints),
+            ..Self::new(self.program.clone(), self.registers)
+        };
+    }
+
+    /// Executes up to `max_steps` instructions, stopping early at breakpoints if `use_breakpoints` is set.
+    fn run(&mut self, max_steps: u64, use_breakpoints: bool) -> Stop {
+        for _ in 0..max_steps {
+            if self.machine.is_halted() {
+                return Stop::Halted;
+            }
+
+            let before = self.machine.registers;
+            self.profile[self.machine.pc] += 1;
+            let output = &mut self.output;
+            self.machine.step(&mut |v| {
+                println!("out: {ANSI_BOLD}{v}{ANSI_RESET}");
+                output.push(v);
+                Control::Continue
+            });
+
+            if use_breakpoints {
+                if let Some(stop) = self.check_breakpoints(&before) {
+                    return stop;
+                }
+            }
+        }
+
+        if self.machine.is_halted() {
+            Stop::Halted
+        } else {
+            Stop::Steps
+        }
+    }
+
+    fn check_breakpoints(&self, before: &[i64; 4]) -> Option<Stop> {
+        self.breakpoints
+            .iter()
+            .enumerate()
+            .find_map(|(id, b)| match (*b)? {
+                Breakpoint::Pc(pc) => (self.machine.pc == pc).then_some(Stop::Breakpoint(id)),
+                Breakpoint::Condition(r, cmp, n) => cmp
+                    .holds(self.machine[r], n)
+                    .then_some(Stop::Breakpoint(id)),
+                Breakpoint::Watch(r) => {
+                    let (old, new) = (before[r as usize], self.machine[r]);
+                    (old != new).then_some(Stop::Watchpoint(id, old, new))
+                }
+            })
+    }
+
+    /// Executes a command. Returns `false` once the debugger should exit.
+    fn execute(&mut self, command: Command) -> bool {
+        match command {
+            Command::Step(n) => {
+                let stop = self.run(n, false);
+                self.report(&stop);
+            }
+            Command::Continue(n) => {
+                let stop = self.run(n.unwrap_or(MAX_CONTINUE_STEPS), true);
+                self.report(&stop);
+            }
+            Command::Break(b) => {
+                if let Breakpoint::Pc(pc) = b {
+                    if pc >= self.program.len() {
+                        println!("pc {pc} is outside the program");
+                        return true;
+                    }
+                }
+                println!("{}: {b}", self.breakpoints.len());
+                self.breakpoints.push(Some(b));
+            }
+            Command::Delete(id) => match self.breakpoints.get_mut(id).and_then(Option::take) {
+                Some(b) => println!("deleted {id}: {b}"),
+                None => println!("no breakpoint with id {id}"),
+            },
+            Command::Info => {
+                if self.breakpoints.iter().all(Option::is_none) {
+                    println!("no breakpoints or watchpoints");
+                }
+                for (id, b) in self.breakpoints.iter().enumerate() {
+                    if let Some(b) = b {
+                        println!("{id}: {b}");
+                    }
+                }
+            }
+            Command::Registers => self.print_registers(),
+            Command::Set(r, n) => {
+                self.machine[r] = n;
+                self.print_registers();
+            }
+            Command::List => self.print_listing(),
+            Command::Profile => self.print_profile(),
+            Command::Output => {
+                let output: Vec<String> = self.output.iter().map(ToString::to_string).collect();
+                println!("{}", output.join(","));
+            }
+            Command::Reset => {
+                self.reset();
+                println!("restarted");
+                self.print_registers();
+            }
+            Command::Help => println!("{HELP}"),
+            Command::Quit => return false,
+        }
+        true
+    }
+
+    fn report(&self, stop: &Stop) {
+        match stop {
+            Stop::Halted => println!("program halted after {} steps", self.machine.steps),
+            Stop::Steps => {}
+            Stop::Breakpoint(id) => {
+                println!("hit {id}: {}", self.breakpoints[*id].unwrap());
+            }
+            Stop::Watchpoint(id, old, new) => {
+                println!(
+                    "hit {id}: {}, {old} -> {new}",
+                    self.breakpoints[*id].unwrap()
+                );
+            }
+        }
+        self.print_registers();
+        if let Some(instruction) = self.machine.program.get(self.machine.pc) {
+            println!(
+                "{ANSI_BOLD}>{:>4}{ANSI_RESET}  {instruction}",
+                self.machine.pc
+            );
+        }
+    }
+
+    fn print_registers(&self) {
+        let registers: Vec<String> = Register::ALL
+            .iter()
+            .map(|&r| format!("{r}={}", self.machine[r]))
+            .collect();
+        println!(
+            "{}  pc={} steps={}",
+            registers.join(" "),
+            self.machine.pc,
+            self.machine.steps
+        );
+    }
+
+    fn print_listing(&self) {
+        for (pc, instruction) in self.machine.program.iter().enumerate() {
+            let marker = if pc == self.machine.pc { '>' } else { ' ' };
+            let breakpoint = if self.breakpoints.contains(&Some(Breakpoint::Pc(pc))) {
+                '*'
+            } else {
+                ' '
+            };
+            let line = format!("{marker}{breakpoint}{pc:>4}  {instruction}");
+            if *instruction == self.program[pc] {
+                println!("{line}");
+            } else {
+                println!(
+                    "{line:<24}{ANSI_ITALIC}(toggled from {}){ANSI_RESET}",
+                    self.program[pc]
+                );
+            }
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn print_profile(&self) {
+        let total: u64 = self.profile.iter().sum();
+        for (pc, &count) in self.profile.iter().enumerate() {
+            let share = if total == 0 {
+                0.0
+            } else {
+                count as f64 * 100.0 / total as f64
+            };
+            println!(
+                "{pc:>4}  {:<16}{count:>12}  {share:>5.1}%",
+                self.machine.program[pc].to_string()
+            );
+        }
+        println!("total {total:>32}");
+    }
+}
+
+fn parse_args() -> Result<(String, [i64; 4]), pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let mut registers = [0; 4];
+    for (r, flag) in registers.iter_mut().zip(["-a", "-b", "-c", "-d"]) {
+        *r = args.opt_value_from_str(flag)?.unwrap_or(0);
+    }
+    let path = args.free_from_str()?;
+    Ok((path, registers))
+}
+
+fn main() {
+    let (path, registers) = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            eprintln!("Usage: assembunny-debugger <program> [-a <n>] [-b <n>] [-c <n>] [-d <n>]");
+            process::exit(1);
+        }
+    };
+
+    let input = fs::read_to_string(&path).unwrap_or_else(|e| {
+        eprintln!("Could not open {path}: {e}");
+        process::exit(1);
+    });
+    let program = assembunny::asm::assemble_program(&input).unwrap_or_else(|e| {
+        eprintln!("{path}: {e}");
+        process::exit(1);
+    });
+
+    println!(
+        "Loaded {} instructions from {path}. Type `help` for a list of commands.",
+        program.len()
+    );
+    let mut debugger = Debugger::new(program, registers);
+    debugger.print_registers();
+
+    let mut last = None;
+    let mut lines = stdin().lock().lines();
+    loop {
+        print!("(bunny) ");
+        let _ = stdout().flush();
+
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        let command = if line.trim().is_empty() {
+            match last {
+                Some(command) => command,
+                None => continue,
+            }
+        } else {
+            match line.parse::<Command>() {
+                Ok(command) => command,
+                Err(e) => {
+                    println!("{e}");
+                    continue;
+                }
+            }
+        };
+
+        last = Some(command);
+        if !debugger.execute(command) {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debugger(a: i64) -> Debugger {
+        let input = advent_of_code::template::read_file("examples", advent_of_code::day!(23));
+        Debugger::new(assembunny::parse(&input).unwrap(), [a, 0, 0, 0])
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!("s".parse(), Ok(Command::Step(1)));
+        assert_eq!("continue 50".parse(), Ok(Command::Continue(Some(50))));
+        assert_eq!(
+            "b if a >= 3".parse(),
+            Ok(Command::Break(Breakpoint::Condition(
+                Register::A,
+                Cmp::Ge,
+                3
+            )))
+        );
+        assert_eq!(
+            "w c".parse(),
+            Ok(Command::Break(Breakpoint::Watch(Register::C)))
+        );
+        assert!("b if e == 1".parse::<Command>().is_err());
+        assert!("jump 3".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn stops_at_breakpoints() {
+        let mut debugger = debugger(0);
+        debugger.execute(Command::Break(Breakpoint::Pc(4)));
+        assert_eq!(debugger.run(100, true), Stop::Breakpoint(0));
+        assert_eq!(debugger.machine.pc, 4);
+        assert_eq!(debugger.run(100, false), Stop::Halted);
+        assert_eq!(debugger.machine[Register::A], 3);
+        // the toggled `jnz 1 a` at pc 4 jumps past the end.
+        assert_eq!(debugger.profile, vec![1, 1, 1, 1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn stops_at_conditions_and_watchpoints() {
+        let mut debugger = debugger(0);
+        debugger.execute(Command::Break(Breakpoint::Watch(Register::A)));
+        assert_eq!(debugger.run(100, true), Stop::Watchpoint(0, 0, 2));
+
+        debugger.execute(Command::Delete(0));
+        debugger.execute(Command::Break(Breakpoint::Condition(
+            Register::A,
+            Cmp::Eq,
+            3,
+        )));
+        assert_eq!(debugger.run(100, true), Stop::Breakpoint(1));
+        assert_eq!(debugger.machine.pc, 4);
+    }
+
+    #[test]
+    fn resets_program_after_toggles() {
+        let mut debugger = debugger(0);
+        debugger.execute(Command::Break(Breakpoint::Pc(3)));
+        debugger.run(100, false);
+        assert_ne!(debugger.machine.program, debugger.program);
+
+        debugger.execute(Command::Reset);
+        assert_eq!(debugger.machine.program, debugger.program);
+        assert_eq!(debugger.machine.pc, 0);
+        assert_eq!(debugger.breakpoints.len(), 1);
+    }
+}
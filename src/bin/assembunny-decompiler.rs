@@ -0,0 +1,42 @@
+//! Prints the pseudo-code, the control-flow graph or a labelled listing of an assembunny program.
+//!
+//! Usage: `cargo bunny-decompile <program> [--dot | --asm]`. Render graphs with e.g. `dot -Tsvg`.
+
+use std::{fs, process};
+
+use advent_of_code::assembunny::{self, cfg::Cfg};
+
+fn main() {
+    let mut args = pico_args::Arguments::from_env();
+    let dot = args.contains("--dot");
+    let asm = args.contains("--asm");
+    let path: String = match args.free_from_str() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            eprintln!("Usage: assembunny-decompiler <program> [--dot | --asm]");
+            process::exit(1);
+        }
+    };
+
+    let input = fs::read_to_string(&path).unwrap_or_else(|e| {
+        eprintln!("Could not open {path}: {e}");
+        process::exit(1);
+    });
+    let program = assembunny::asm::assemble_program(&input).unwrap_or_else(|e| {
+        eprintln!("{path}: {e}");
+        process::exit(1);
+    });
+
+    if asm {
+        print!("{}", assembunny::asm::disassemble(&program));
+        return;
+    }
+
+    let cfg = Cfg::new(&program);
+    if dot {
+        print!("{}", cfg.to_dot(&program));
+    } else {
+        print!("{}", cfg.decompile());
+    }
+}
@@ -0,0 +1,44 @@
+//! Walks the Day 2 instructions on a keypad of your own.
+//!
+//! Usage: `cargo keypad --keypad <file> [--input <file>]` prints the code for the keypad drawn in
+//! `<file>`, where every character but a space is a key, reading the instructions from
+//! `data/inputs/02.txt` by default.
+
+use std::{fs, process};
+
+use advent_of_code::keypad::{parse_instructions, Keypad};
+
+const USAGE: &str = "Usage: keypad --keypad <file> [--input <file>]";
+
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("Error: {message}");
+    eprintln!("{USAGE}");
+    process::exit(1);
+}
+
+fn read(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Could not open {path}: {e}");
+        process::exit(1);
+    })
+}
+
+fn main() {
+    let mut args = pico_args::Arguments::from_env();
+
+    let keypad: String = args.value_from_str("--keypad").unwrap_or_else(|e| fail(e));
+    let path: String = args
+        .opt_value_from_str("--input")
+        .unwrap_or_else(|e| fail(e))
+        .unwrap_or_else(|| "data/inputs/02.txt".into());
+
+    let keypad: Keypad = read(&keypad).parse().unwrap_or_else(|e| {
+        eprintln!("{keypad}: {e:#}");
+        process::exit(1);
+    });
+    let instructions = parse_instructions(&read(&path)).unwrap_or_else(|e| {
+        eprintln!("{path}: {e:#}");
+        process::exit(1);
+    });
+    println!("{}", keypad.code(&instructions));
+}
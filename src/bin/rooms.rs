@@ -0,0 +1,75 @@
+//! Searches the decrypted names of the real rooms of day 4, or generates valid room lines.
+//!
+//! Usage: `cargo rooms <query> [--regex] [--crack] [--input <file>]` prints the sector ID and name
+//! of every match, reading `data/inputs/04.txt` by default. `--crack` decrypts the names by
+//! letter frequencies instead of their sector IDs. `cargo rooms --generate <count> [--seed <n>]`
+//! prints random rooms with correct checksums.
+
+use std::{fs, process};
+
+use advent_of_code::{
+    error::parse_lines,
+    rooms::{search, Generator, Query, Room},
+};
+use regex::Regex;
+
+const USAGE: &str = "Usage: rooms <query> [--regex] [--crack] [--input <file>]
+       rooms --generate <count> [--seed <n>]";
+
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("Error: {message}");
+    eprintln!("{USAGE}");
+    process::exit(1);
+}
+
+fn main() {
+    let mut args = pico_args::Arguments::from_env();
+
+    let generate: Option<usize> = args
+        .opt_value_from_str("--generate")
+        .unwrap_or_else(|e| fail(e));
+    if let Some(count) = generate {
+        let seed = args
+            .opt_value_from_str("--seed")
+            .unwrap_or_else(|e| fail(e));
+        for line in Generator::new(seed.unwrap_or(2016)).take(count) {
+            println!("{line}");
+        }
+        return;
+    }
+
+    let regex = args.contains("--regex");
+    let crack = args.contains("--crack");
+    let path: String = args
+        .opt_value_from_str("--input")
+        .unwrap_or_else(|e| fail(e))
+        .unwrap_or_else(|| "data/inputs/04.txt".into());
+    let query: String = args.free_from_str().unwrap_or_else(|e| fail(e));
+    let query = if regex {
+        Query::Regex(Regex::new(&query).unwrap_or_else(|e| fail(e)))
+    } else {
+        Query::Keyword(query)
+    };
+
+    let input = fs::read_to_string(&path).unwrap_or_else(|e| {
+        eprintln!("Could not open {path}: {e}");
+        process::exit(1);
+    });
+    let rooms: Vec<Room> = parse_lines(&input).unwrap_or_else(|e| {
+        eprintln!("{path}: {e:#}");
+        process::exit(1);
+    });
+    if crack {
+        for room in rooms.iter().filter(|room| room.is_valid()) {
+            let cracked = room.crack();
+            if query.matches(&cracked.plaintext) {
+                let sure = cracked.confidence * 100.0;
+                println!("{:>4}  {} ({sure:.0}% sure)", room.id, cracked.plaintext);
+            }
+        }
+        return;
+    }
+    for (room, name) in search(&rooms, &query) {
+        println!("{:>4}  {name}", room.id);
+    }
+}
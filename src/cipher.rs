@@ -0,0 +1,126 @@
+//! Shift ciphers, which move every letter a fixed number of places through the alphabet.
+//!
+//! A [`Shift`] encrypts and decrypts with a known key. [`crack`] finds the key of an unknown
+//! shift by scoring every candidate against English letter frequencies. Letters keep their case
+//! and everything else passes through unchanged.
+
+/// How often each letter occurs in English text, in percent.
+const ENGLISH: [f64; 26] = [
+    8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406,
+    6.749, 7.507, 1.929, 0.095, 5.987, 6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+];
+
+/// A shift of every letter by `0..26` places.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shift(u8);
+
+impl Shift {
+    /// A shift by `key` places, where negative keys shift backwards.
+    pub fn new(key: i64) -> Self {
+        Self(key.rem_euclid(26) as u8)
+    }
+
+    /// The number of places letters move forward, in `0..26`.
+    pub fn key(self) -> u8 {
+        self.0
+    }
+
+    /// The shift undoing this one.
+    #[must_use]
+    pub fn inverse(self) -> Self {
+        Self((26 - self.0) % 26)
+    }
+
+    fn rotate(self, c: char) -> char {
+        let base = match c {
+            'a'..='z' => b'a',
+            'A'..='Z' => b'A',
+            _ => return c,
+        };
+        char::from(base + (c as u8 - base + self.0) % 26)
+    }
+
+    pub fn encrypt(self, plaintext: &str) -> String {
+        plaintext.chars().map(|c| self.rotate(c)).collect()
+    }
+
+    pub fn decrypt(self, ciphertext: &str) -> String {
+        self.inverse().encrypt(ciphertext)
+    }
+}
+
+/// The most likely decryption of a text encrypted with an unknown [`Shift`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cracked {
+    pub shift: Shift,
+    pub plaintext: String,
+    /// How likely `shift` is the right key compared to all others, from 0 to 1.
+    pub confidence: f64,
+}
+
+/// Finds the shift that makes `ciphertext` look most like English.
+///
+/// Every key is scored by the log-likelihood of the decrypted letters under English letter
+/// frequencies. The confidence is the share of the best key when the likelihoods are normalized
+/// over all keys, so short or unusual texts get a low confidence.
+pub fn crack(ciphertext: &str) -> Cracked {
+    let mut counts = [0u32; 26];
+    for c in ciphertext.chars().filter(char::is_ascii_alphabetic) {
+        counts[usize::from(c.to_ascii_lowercase() as u8 - b'a')] += 1;
+    }
+
+    // decrypting with key k turns ciphertext letter i into plaintext letter i - k.
+    let scores: Vec<f64> = (0..26)
+        .map(|k| {
+            (0..26)
+                .map(|i| f64::from(counts[i]) * ENGLISH[(i + 26 - k) % 26].ln())
+                .sum()
+        })
+        .collect();
+    let (best, max) = scores
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap();
+    let total: f64 = scores.iter().map(|s| (s - max).exp()).sum();
+
+    let shift = Shift(best as u8);
+    Cracked {
+        shift,
+        plaintext: shift.decrypt(ciphertext),
+        confidence: 1.0 / total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crack, Shift};
+
+    #[test]
+    fn encrypts_and_decrypts() {
+        let shift = Shift::new(3);
+        assert_eq!(shift.encrypt("Hello, World! xyz"), "Khoor, Zruog! abc");
+        assert_eq!(shift.decrypt("Khoor, Zruog! abc"), "Hello, World! xyz");
+        assert_eq!(Shift::new(-23), shift);
+        assert_eq!(Shift::new(343).key(), 5);
+        assert_eq!(shift.inverse().key(), 23);
+        assert_eq!(Shift::new(0).inverse().key(), 0);
+    }
+
+    #[test]
+    fn cracks_unknown_shifts() {
+        let plaintext = "The quick brown fox jumps over the lazy dog while the bunnies guard \
+                         the headquarters of the Easter Bunny";
+        for key in [0, 1, 13, 25] {
+            let cracked = crack(&Shift::new(key).encrypt(plaintext));
+            assert_eq!(cracked.shift, Shift::new(key));
+            assert_eq!(cracked.plaintext, plaintext);
+            assert!(cracked.confidence > 0.99, "{}", cracked.confidence);
+        }
+
+        let cracked = crack("zzz");
+        assert!(cracked.confidence < 0.5, "{}", cracked.confidence);
+        assert_eq!(crack("").plaintext, "");
+    }
+}
@@ -0,0 +1,356 @@
+//! A parse error shared by the solutions, pointing at the offending line and columns.
+//!
+//! Parsers build a [`ParseError`] for the text they were handed and mark the part that failed with
+//! [`ParseError::within`]. The line helpers ([`parse_lines`], [`map_lines`]) then attach the line
+//! number, so `{}` prints a one-line summary and `{:#}` a snippet with a caret:
+//!
+//! ```text
+//! line 3, column 8: expected a number, found `abc`
+//!   |
+//! 3 | rect 3xabc
+//!   |        ^^^
+//! ```
+
+use std::{
+    char::ParseCharError,
+    convert::Infallible,
+    error::Error,
+    fmt::Display,
+    num::{ParseFloatError, ParseIntError},
+    ops::Range,
+    str::{FromStr, ParseBoolError},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    Message(String),
+    /// A conversion error from the standard library, which does not know the text it failed on.
+    Conversion(String),
+    Expected {
+        expected: String,
+        found: String,
+    },
+}
+
+/// An error while parsing puzzle input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    kind: Kind,
+    /// The 1-based line number in the input.
+    line: Option<usize>,
+    /// The text the span refers to, usually the whole line.
+    source: Option<String>,
+    /// The byte range of the offending part.
+    span: Option<Range<usize>>,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self::from_kind(Kind::Message(message.into()))
+    }
+
+    /// An error for finding `found` where `expected` should have been.
+    pub fn expected(expected: impl Into<String>, found: &str) -> Self {
+        Self::from_kind(Kind::Expected {
+            expected: expected.into(),
+            found: found.into(),
+        })
+    }
+
+    fn from_kind(kind: Kind) -> Self {
+        Self {
+            kind,
+            line: None,
+            source: None,
+            span: None,
+        }
+    }
+
+    /// Marks `part` of `text` as the offending input.
+    ///
+    /// If the error already points into a smaller piece of input, `part` must be that piece and
+    /// the span is moved along with it. This lets a parser of list items report columns relative
+    /// to the whole line.
+    #[must_use]
+    pub fn within(mut self, text: &str, part: &str) -> Self {
+        let Some(offset) = offset_in(text, part) else {
+            return self;
+        };
+        self.span = Some(match self.span {
+            Some(span) if self.source.as_deref() == Some(part) => {
+                span.start + offset..span.end + offset
+            }
+            _ => offset..offset + part.len(),
+        });
+        self.source = Some(text.into());
+        self
+    }
+
+    /// Attaches the 1-based line number and its text, unless the error already knows its line.
+    #[must_use]
+    pub fn at_line(mut self, number: usize, line: &str) -> Self {
+        if self.line.is_none() {
+            self.line = Some(number);
+            if self.source.is_none() {
+                self.source = Some(line.into());
+            }
+        }
+        self
+    }
+
+    /// Attaches the line of a multi-line `block` the error points into, where `first_line` is
+    /// the number of the block's first line.
+    #[must_use]
+    pub fn in_block(mut self, first_line: usize, block: &str) -> Self {
+        let first = block.lines().next().unwrap_or_default();
+        let start = match (&self.source, &self.span) {
+            (Some(source), Some(span)) if self.line.is_none() && source == block => span.start,
+            _ => return self.at_line(first_line, first),
+        };
+        let line_start = block[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line = block[line_start..].lines().next().unwrap_or_default();
+        self.span = self
+            .span
+            .map(|span| span.start - line_start..(span.end - line_start).min(line.len()));
+        self.line = Some(first_line + block[..line_start].matches('\n').count());
+        self.source = Some(line.into());
+        self
+    }
+
+    /// Replaces a bare conversion error with one naming what was expected instead of `found`.
+    pub(crate) fn or_expected(self, expected: impl Into<String>, found: &str) -> Self {
+        match self.kind {
+            Kind::Conversion(_) => Self::expected(expected, found),
+            _ => self,
+        }
+    }
+
+    /// The 1-based line number, if known.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// The 1-based column the offending part starts at, if known.
+    pub fn column(&self) -> Option<usize> {
+        let (source, span) = self.source.as_ref().zip(self.span.as_ref())?;
+        Some(source.get(..span.start)?.chars().count() + 1)
+    }
+
+    pub fn expected_text(&self) -> Option<&str> {
+        match &self.kind {
+            Kind::Expected { expected, .. } => Some(expected),
+            Kind::Message(_) | Kind::Conversion(_) => None,
+        }
+    }
+
+    pub fn found_text(&self) -> Option<&str> {
+        match &self.kind {
+            Kind::Expected { found, .. } => Some(found),
+            Kind::Message(_) | Kind::Conversion(_) => None,
+        }
+    }
+
+    /// Renders the source line with carets under the offending part.
+    fn write_snippet(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(source) = &self.source else {
+            return Ok(());
+        };
+        let number = self.line.map(|n| n.to_string()).unwrap_or_default();
+        let gutter = " ".repeat(number.len());
+        write!(f, "\n{gutter} |\n{number} | {source}")?;
+
+        if let Some(span) = &self.span {
+            let (Some(before), Some(part)) = (source.get(..span.start), source.get(span.clone()))
+            else {
+                return Ok(());
+            };
+            let pad = " ".repeat(before.chars().count());
+            let carets = "^".repeat(part.chars().count().max(1));
+            write!(f, "\n{gutter} | {pad}{carets}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds where `part` starts in `text`, preferring its actual position if it is a subslice.
+fn offset_in(text: &str, part: &str) -> Option<usize> {
+    let offset = (part.as_ptr() as usize).wrapping_sub(text.as_ptr() as usize);
+    if offset <= text.len() && offset + part.len() <= text.len() {
+        Some(offset)
+    } else {
+        text.find(part)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column()) {
+            (Some(line), Some(column)) => write!(f, "line {line}, column {column}: ")?,
+            (Some(line), None) => write!(f, "line {line}: ")?,
+            (None, Some(column)) => write!(f, "column {column}: ")?,
+            (None, None) => {}
+        }
+        match &self.kind {
+            Kind::Message(message) | Kind::Conversion(message) => write!(f, "{message}")?,
+            Kind::Expected { expected, found } if found.is_empty() => {
+                write!(f, "expected {expected}, found nothing")?;
+            }
+            Kind::Expected { expected, found } => {
+                write!(f, "expected {expected}, found `{found}`")?
+            }
+        }
+        if f.alternate() {
+            self.write_snippet(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for ParseError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+macro_rules! conversion_errors {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for ParseError {
+                fn from(e: $t) -> Self {
+                    Self::from_kind(Kind::Conversion(e.to_string()))
+                }
+            }
+        )*
+    };
+}
+
+conversion_errors!(
+    ParseIntError,
+    ParseFloatError,
+    ParseCharError,
+    ParseBoolError
+);
+
+impl From<Infallible> for ParseError {
+    fn from(e: Infallible) -> Self {
+        match e {}
+    }
+}
+
+/// Parses `part` as a `T`, pointing the error at `part` within `text`.
+pub fn parse_in<T: FromStr>(text: &str, part: &str, expected: &str) -> Result<T, ParseError> {
+    part.parse()
+        .map_err(|_| ParseError::expected(expected, part).within(text, part))
+}
+
+/// Applies `f` to every non-blank line, attaching the line to its errors.
+pub fn map_lines<'a, T, E: Into<ParseError>>(
+    input: &'a str,
+    mut f: impl FnMut(&'a str) -> Result<T, E> + 'a,
+) -> impl Iterator<Item = Result<T, ParseError>> + 'a {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(move |(i, line)| f(line).map_err(|e| e.into().at_line(i + 1, line)))
+}
+
+/// Parses every non-blank line as a `T`.
+pub fn parse_lines<T>(input: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: Into<ParseError>,
+{
+    map_lines(input, str::parse).collect()
+}
+
+/// Parses the items of `text` separated by `separator`, pointing errors into `text`.
+pub fn parse_separated<T>(text: &str, separator: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: Into<ParseError>,
+{
+    text.split(separator)
+        .map(|item| {
+            item.parse()
+                .map_err(|e: T::Err| e.into().within(text, item))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_in, parse_lines, parse_separated, ParseError};
+
+    #[derive(Debug, PartialEq)]
+    struct Rect(u32, u32);
+
+    impl std::str::FromStr for Rect {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let dims = s
+                .strip_prefix("rect ")
+                .ok_or_else(|| ParseError::expected("`rect`", s).within(s, s))?;
+            let (x, y) = dims
+                .split_once('x')
+                .ok_or_else(|| ParseError::expected("`<x>x<y>`", dims).within(s, dims))?;
+            Ok(Self(
+                parse_in(s, x, "a number")?,
+                parse_in(s, y, "a number")?,
+            ))
+        }
+    }
+
+    #[test]
+    fn points_at_the_offending_part() {
+        let err = parse_lines::<Rect>("rect 1x2\n\nrect 3xabc\n").unwrap_err();
+        assert_eq!(err.line(), Some(3));
+        assert_eq!(err.column(), Some(8));
+        assert_eq!(err.expected_text(), Some("a number"));
+        assert_eq!(err.found_text(), Some("abc"));
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 8: expected a number, found `abc`"
+        );
+        assert_eq!(
+            format!("{err:#}"),
+            "line 3, column 8: expected a number, found `abc`\n  |\n3 | rect 3xabc\n  |        ^^^"
+        );
+    }
+
+    #[test]
+    fn moves_item_spans_into_the_line() {
+        let err = parse_separated::<Rect>("rect 1x2, rect 3x, rect 4x5", ", ").unwrap_err();
+        assert_eq!(err.column(), Some(18));
+        assert_eq!(err.found_text(), Some(""));
+
+        let err = parse_separated::<u32>("1, 2, x", ", ")
+            .unwrap_err()
+            .at_line(4, "1, 2, x");
+        assert_eq!(
+            format!("{err:#}"),
+            "line 4, column 7: invalid digit found in string\n  |\n4 | 1, 2, x\n  |       ^"
+        );
+    }
+
+    #[test]
+    fn keeps_plain_messages() {
+        let err = ParseError::new("no floors").at_line(1, "The first floor");
+        assert_eq!(err.column(), None);
+        assert_eq!(err.to_string(), "line 1: no floors");
+        assert_eq!(
+            format!("{err:#}"),
+            "line 1: no floors\n  |\n1 | The first floor"
+        );
+        assert_eq!(ParseError::new("empty").to_string(), "empty");
+    }
+}
@@ -0,0 +1,552 @@
+//! Two-dimensional grids for the map puzzles.
+//!
+//! [`Grid`] stores one value per cell, [`BitGrid`] packs on/off cells into 64-bit words. Both are
+//! indexed with `(x, y)` points, where `y` counts rows from the top.
+
+use std::{
+    fmt::Display,
+    ops::{BitAnd, BitOr, BitXor, Index, IndexMut},
+};
+
+use crate::error::ParseError;
+
+/// A cell position as `(x, y)`.
+pub type Point = (usize, usize);
+
+const NEIGHBOURS4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const NEIGHBOURS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+fn neighbours(
+    width: usize,
+    height: usize,
+    (x, y): Point,
+    offsets: &'static [(isize, isize)],
+) -> impl Iterator<Item = Point> {
+    offsets.iter().filter_map(move |&(dx, dy)| {
+        let x = x.checked_add_signed(dx).filter(|&x| x < width)?;
+        let y = y.checked_add_signed(dy).filter(|&y| y < height)?;
+        Some((x, y))
+    })
+}
+
+/// A line of a char map with its 1-based line number.
+type Line<'a> = (usize, &'a str);
+
+/// Splits a char map into its non-empty lines, checking that they all have the same length.
+fn parse_lines(input: &str) -> Result<(usize, Vec<Line<'_>>), ParseError> {
+    let lines: Vec<Line> = input
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(i, l)| (i + 1, l))
+        .collect();
+    let width = lines.first().map_or(0, |(_, l)| l.chars().count());
+    for &(number, line) in &lines {
+        let len = line.chars().count();
+        if len != width {
+            return Err(
+                ParseError::new(format!("expected {width} characters, found {len}"))
+                    .at_line(number, line),
+            );
+        }
+    }
+    Ok((width, lines))
+}
+
+/// The character starting at byte `i` of `line`, as a slice to point errors at.
+fn char_at(line: &str, i: usize) -> &str {
+    &line[i..i + line[i..].chars().next().map_or(0, char::len_utf8)]
+}
+
+/// A dense grid with one `T` per cell.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Point) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut f)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses a char map, converting every character with `f`. Errors name the offending cell.
+    pub fn parse_chars<E: Display>(
+        input: &str,
+        mut f: impl FnMut(Point, char) -> Result<T, E>,
+    ) -> Result<Self, ParseError> {
+        let (width, lines) = parse_lines(input)?;
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (y, &(number, line)) in lines.iter().enumerate() {
+            for (x, (i, c)) in line.char_indices().enumerate() {
+                cells.push(f((x, y), c).map_err(|e| {
+                    ParseError::new(e.to_string())
+                        .within(line, char_at(line, i))
+                        .at_line(number, line)
+                })?);
+            }
+        }
+        Ok(Self {
+            width,
+            height: lines.len(),
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Point) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.contains(p)
+            .then(|| &self.cells[p.1 * self.width + p.0])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.contains(p)
+            .then(|| &mut self.cells[p.1 * self.width + p.0])
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// All points in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// All cells with their points in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(&self.cells)
+    }
+
+    /// The points above, left, right and below `p` that lie in the grid.
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = Point> {
+        neighbours(self.width, self.height, p, &NEIGHBOURS4)
+    }
+
+    /// Like [`Grid::neighbours4`], including the diagonals.
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = Point> {
+        neighbours(self.width, self.height, p, &NEIGHBOURS8)
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(&mut f).collect(),
+        }
+    }
+
+    /// Rotates row `y` to the right by `by` cells.
+    pub fn rotate_row(&mut self, y: usize, by: usize) {
+        let width = self.width;
+        self.cells[y * width..(y + 1) * width].rotate_right(by % width.max(1));
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Rotates column `x` down by `by` cells.
+    pub fn rotate_column(&mut self, x: usize, by: usize) {
+        let mut column: Vec<T> = (0..self.height).map(|y| self[(x, y)].clone()).collect();
+        column.rotate_right(by % self.height.max(1));
+        for (y, value) in column.into_iter().enumerate() {
+            self[(x, y)] = value;
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_fn(self.height, self.width, |(x, y)| self[(y, x)].clone())
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &Self::Output {
+        assert!(self.contains(p), "{p:?} is outside the grid");
+        &self.cells[p.1 * self.width + p.0]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut Self::Output {
+        assert!(self.contains(p), "{p:?} is outside the grid");
+        &mut self.cells[p.1 * self.width + p.0]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A grid of on/off cells, packed 64 to a word. Rows start on a word boundary.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    /// The number of words per row.
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// A grid with all cells off.
+    pub fn new(width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            stride,
+            words: vec![0; stride * height],
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Point) -> bool) -> Self {
+        let mut grid = Self::new(width, height);
+        for p in grid.points() {
+            if f(p) {
+                grid.set(p, true);
+            }
+        }
+        grid
+    }
+
+    /// Parses a char map in which `on` marks set cells and `off` cleared ones.
+    pub fn parse_chars(input: &str, on: char, off: char) -> Result<Self, ParseError> {
+        let (width, lines) = parse_lines(input)?;
+        let mut grid = Self::new(width, lines.len());
+        for (y, &(number, line)) in lines.iter().enumerate() {
+            for (x, (i, c)) in line.char_indices().enumerate() {
+                match c {
+                    c if c == on => grid.set((x, y), true),
+                    c if c == off => {}
+                    _ => {
+                        let found = char_at(line, i);
+                        return Err(ParseError::expected(format!("`{on}` or `{off}`"), found)
+                            .within(line, found)
+                            .at_line(number, line));
+                    }
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Point) -> bool {
+        x < self.width && y < self.height
+    }
+
+    fn locate(&self, p: Point) -> (usize, u64) {
+        assert!(self.contains(p), "{p:?} is outside the grid");
+        (p.1 * self.stride + p.0 / 64, 1 << (p.0 % 64))
+    }
+
+    /// Whether the cell at `p` is on. Panics if `p` lies outside the grid.
+    pub fn get(&self, p: Point) -> bool {
+        let (word, bit) = self.locate(p);
+        self.words[word] & bit != 0
+    }
+
+    pub fn set(&mut self, p: Point, on: bool) {
+        let (word, bit) = self.locate(p);
+        if on {
+            self.words[word] |= bit;
+        } else {
+            self.words[word] &= !bit;
+        }
+    }
+
+    pub fn toggle(&mut self, p: Point) {
+        let (word, bit) = self.locate(p);
+        self.words[word] ^= bit;
+    }
+
+    /// The number of cells that are on.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// All points in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// The points above, left, right and below `p` that lie in the grid.
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = Point> {
+        neighbours(self.width, self.height, p, &NEIGHBOURS4)
+    }
+
+    /// Like [`BitGrid::neighbours4`], including the diagonals.
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = Point> {
+        neighbours(self.width, self.height, p, &NEIGHBOURS8)
+    }
+
+    /// Rotates row `y` to the right by `by` cells.
+    pub fn rotate_row(&mut self, y: usize, by: usize) {
+        let row: Vec<bool> = (0..self.width).map(|x| self.get((x, y))).collect();
+        for (x, on) in row.into_iter().enumerate() {
+            self.set(((x + by) % self.width, y), on);
+        }
+    }
+
+    /// Rotates column `x` down by `by` cells.
+    pub fn rotate_column(&mut self, x: usize, by: usize) {
+        let column: Vec<bool> = (0..self.height).map(|y| self.get((x, y))).collect();
+        for (y, on) in column.into_iter().enumerate() {
+            self.set((x, (y + by) % self.height), on);
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_fn(self.height, self.width, |(x, y)| self.get((y, x)))
+    }
+
+    /// Moves every cell by `(dx, dy)`. Cells that leave the grid are dropped, the ones that
+    /// come in are off. Works on whole words, which makes it the fast path for automata.
+    pub fn shift(&self, dx: isize, dy: isize) -> Self {
+        let mut out = Self::new(self.width, self.height);
+        let (words, bits) = (dx.unsigned_abs() / 64, (dx.unsigned_abs() % 64) as u32);
+        for y in 0..self.height {
+            let Some(to) = y.checked_add_signed(dy).filter(|&to| to < self.height) else {
+                continue;
+            };
+            let row = &self.words[y * self.stride..(y + 1) * self.stride];
+            let word = |i: Option<usize>| i.and_then(|i| row.get(i)).copied().unwrap_or(0);
+            for (i, w) in out.words[to * self.stride..(to + 1) * self.stride]
+                .iter_mut()
+                .enumerate()
+            {
+                // cells move to higher bits for positive `dx`.
+                *w = if dx >= 0 {
+                    let src = i.checked_sub(words);
+                    let carry = src.and_then(|s| s.checked_sub(1));
+                    (word(src) << bits) | (word(carry) >> 1 >> (63 - bits))
+                } else {
+                    let src = Some(i + words);
+                    (word(src) >> bits) | (word(Some(i + words + 1)) << 1 << (63 - bits))
+                };
+            }
+        }
+        out.clear_padding();
+        out
+    }
+
+    /// Clears the bits past the last column, which must stay off for counting.
+    fn clear_padding(&mut self) {
+        if !self.width.is_multiple_of(64) {
+            let mask = (1 << (self.width % 64)) - 1;
+            for row in self.words.chunks_mut(self.stride) {
+                row[self.stride - 1] &= mask;
+            }
+        }
+    }
+
+    fn zip_words(mut self, rhs: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        assert_eq!(
+            (self.width, self.height),
+            (rhs.width, rhs.height),
+            "grids must have the same size"
+        );
+        for (a, &b) in self.words.iter_mut().zip(&rhs.words) {
+            *a = f(*a, b);
+        }
+        self
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($($trait:ident $method:ident $op:tt),*) => {
+        $(
+            impl $trait for BitGrid {
+                type Output = BitGrid;
+
+                fn $method(self, rhs: Self) -> Self::Output {
+                    self.zip_words(&rhs, |a, b| a $op b)
+                }
+            }
+
+            impl $trait for &BitGrid {
+                type Output = BitGrid;
+
+                fn $method(self, rhs: Self) -> Self::Output {
+                    self.clone().zip_words(rhs, |a, b| a $op b)
+                }
+            }
+        )*
+    };
+}
+
+impl_bit_op!(BitAnd bitand &, BitOr bitor |, BitXor bitxor ^);
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                write!(f, "{}", if self.get((x, y)) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitGrid, Grid};
+
+    #[test]
+    fn parses_and_displays_char_maps() {
+        let grid = Grid::parse_chars("ab\ncd\n", |_, c| Ok::<_, String>(c)).unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert_eq!(grid[(1, 0)], 'b');
+        assert_eq!(grid.to_string(), "ab\ncd\n");
+        assert_eq!(grid.transpose().to_string(), "ac\nbd\n");
+
+        let err = Grid::parse_chars("ab\nc\n", |_, c| Ok::<_, String>(c)).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: expected 2 characters, found 1");
+        let err = Grid::parse_chars(
+            "ab\ncd\n",
+            |_, c| {
+                if c == 'd' {
+                    Err("no d")
+                } else {
+                    Ok(c)
+                }
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 2: no d");
+
+        let err = BitGrid::parse_chars("#.\n\n.x\n", '#', '.').unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 3, column 2: expected `#` or `.`, found `x`\n  |\n3 | .x\n  |  ^"
+        );
+    }
+
+    #[test]
+    fn finds_neighbours_inside_bounds() {
+        let grid = Grid::new(3, 2, 0);
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(
+            grid.neighbours8((2, 1)).collect::<Vec<_>>(),
+            [(1, 0), (2, 0), (1, 1)]
+        );
+        assert_eq!(grid.neighbours8((1, 0)).count(), 5);
+    }
+
+    #[test]
+    fn rotates_rows_and_columns() {
+        let mut grid = Grid::from_fn(3, 2, |(x, y)| (b'a' + (3 * y + x) as u8) as char);
+        grid.rotate_row(0, 1);
+        assert_eq!(grid.to_string(), "cab\ndef\n");
+        grid.rotate_column(2, 3);
+        assert_eq!(grid.to_string(), "caf\ndeb\n");
+    }
+
+    #[test]
+    fn packs_bits() {
+        let mut grid = BitGrid::parse_chars("#..\n.#.\n", '#', '.').unwrap();
+        assert_eq!(grid.count_ones(), 2);
+        grid.rotate_row(0, 2);
+        grid.rotate_column(1, 1);
+        assert_eq!(grid.to_string(), ".##\n...\n");
+        assert_eq!(grid.transpose().to_string(), "..\n#.\n#.\n");
+
+        let mut wide = BitGrid::new(130, 2);
+        wide.set((129, 1), true);
+        wide.rotate_row(1, 1);
+        assert!(wide.get((0, 1)));
+        assert_eq!(wide.count_ones(), 1);
+    }
+
+    #[test]
+    fn shifts_across_words() {
+        let grid = BitGrid::from_fn(130, 3, |(x, y)| x % 3 == 0 && y != 1);
+        for (dx, dy) in [
+            (1, 0),
+            (-1, 0),
+            (63, 1),
+            (64, 0),
+            (-65, -1),
+            (129, 0),
+            (-200, 2),
+        ] {
+            let shifted = grid.shift(dx, dy);
+            let expected = BitGrid::from_fn(130, 3, |(x, y)| {
+                match (x.checked_add_signed(-dx), y.checked_add_signed(-dy)) {
+                    (Some(x), Some(y)) if grid.contains((x, y)) => grid.get((x, y)),
+                    _ => false,
+                }
+            });
+            assert_eq!(shifted, expected, "{dx} {dy}");
+        }
+
+        let a = BitGrid::parse_chars("##..\n", '#', '.').unwrap();
+        let b = BitGrid::parse_chars(".##.\n", '#', '.').unwrap();
+        assert_eq!((&a ^ &b).to_string(), "#.#.\n");
+        assert_eq!((&a & &b).to_string(), ".#..\n");
+        assert_eq!((a | b).to_string(), "###.\n");
+    }
+}
@@ -0,0 +1,140 @@
+//! The bathroom keypads of day 2.
+//!
+//! A [`Keypad`] is drawn as text, where every character but a space is a key, so keypads with
+//! holes such as the diamond of part two need no special casing. Instructions are lines of
+//! [`Dir`]s, and [`Keypad::code`] follows them to the key pressed at the end of every line.
+
+use std::str::FromStr;
+
+use crate::{
+    error::{map_lines, ParseError},
+    grid::{Grid, Point},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dir {
+    U,
+    D,
+    L,
+    R,
+}
+
+impl Dir {
+    fn step(self, (x, y): Point) -> Option<Point> {
+        match self {
+            Dir::U => Some((x, y.checked_sub(1)?)),
+            Dir::D => Some((x, y + 1)),
+            Dir::L => Some((x.checked_sub(1)?, y)),
+            Dir::R => Some((x + 1, y)),
+        }
+    }
+}
+
+impl FromStr for Dir {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "U" => Ok(Self::U),
+            "D" => Ok(Self::D),
+            "L" => Ok(Self::L),
+            "R" => Ok(Self::R),
+            _ => Err(ParseError::expected("one of `U`, `D`, `L` or `R`", s)),
+        }
+    }
+}
+
+/// A keypad with holes. Moves into a hole or off the edge are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keypad {
+    keys: Grid<Option<char>>,
+    /// The `5` key, or the first key if there is none.
+    start: Point,
+}
+
+impl FromStr for Keypad {
+    type Err = ParseError;
+
+    fn from_str(layout: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = layout.trim_matches('\n').lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let keys = Grid::from_fn(width, lines.len(), |(x, y)| {
+            lines[y].chars().nth(x).filter(|&c| c != ' ')
+        });
+
+        let mut seen = vec![];
+        for (y, line) in lines.iter().enumerate() {
+            for (i, c) in line.char_indices().filter(|&(_, c)| c != ' ') {
+                if seen.contains(&c) {
+                    let key = &line[i..i + c.len_utf8()];
+                    return Err(ParseError::new(format!("key `{c}` appears twice"))
+                        .within(line, key)
+                        .at_line(y + 1, line));
+                }
+                seen.push(c);
+            }
+        }
+
+        let start = keys
+            .iter()
+            .find(|&(_, &key)| key == Some('5'))
+            .or_else(|| keys.iter().find(|(_, key)| key.is_some()))
+            .map(|(p, _)| p)
+            .ok_or_else(|| ParseError::new("the keypad has no keys"))?;
+        Ok(Self { keys, start })
+    }
+}
+
+impl Keypad {
+    fn key(&self, p: Point) -> Option<char> {
+        self.keys.get(p).copied().flatten()
+    }
+
+    fn walk(&self, from: Point, dirs: &[Dir]) -> Point {
+        dirs.iter().fold(from, |p, dir| match dir.step(p) {
+            Some(next) if self.key(next).is_some() => next,
+            _ => p,
+        })
+    }
+
+    /// The key reached at the end of every line of instructions.
+    pub fn code(&self, instructions: &[Vec<Dir>]) -> String {
+        instructions
+            .iter()
+            .scan(self.start, |p, dirs| {
+                *p = self.walk(*p, dirs);
+                self.key(*p)
+            })
+            .collect()
+    }
+}
+
+/// Parses one line of directions per key.
+pub fn parse_instructions(input: &str) -> Result<Vec<Vec<Dir>>, ParseError> {
+    map_lines(input, |line| {
+        line.char_indices()
+            .map(|(i, c)| {
+                let s = &line[i..i + c.len_utf8()];
+                s.parse::<Dir>().map_err(|e| e.within(line, s))
+            })
+            .collect()
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_instructions, Keypad};
+
+    #[test]
+    fn walks_custom_keypads() {
+        let keypad: Keypad = "123\n4 6\n789".parse().unwrap();
+        assert_eq!(keypad.start, (0, 0));
+        let instructions = parse_instructions("DD\nRU\nRRRUUL").unwrap();
+        assert_eq!(keypad.code(&instructions), "782");
+
+        let err = "12\n21".parse::<Keypad>().unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 1: key `2` appears twice");
+        assert!(" \n ".parse::<Keypad>().is_err());
+    }
+}
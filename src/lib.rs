@@ -1,3 +1,15 @@
+pub mod assembunny;
+pub mod cipher;
+pub mod error;
+pub mod grid;
+pub mod keypad;
+pub mod mining;
+pub mod parse;
+pub mod random;
+pub mod rooms;
+pub mod search;
+pub mod spans;
 pub mod template;
+pub mod visualize;
 
 // Use this file to add helper functions and additional modules.
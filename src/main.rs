@@ -1,4 +1,6 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve, time};
+use advent_of_code::template::commands::{
+    all, download, read, scaffold, solve, time, time_bisect, verify,
+};
 use args::{parse, AppArguments};
 
 #[cfg(feature = "today")]
@@ -7,6 +9,8 @@ use advent_of_code::template::Day;
 use std::process;
 
 mod args {
+    use advent_of_code::template::commands::time_bisect::Limit;
+    use advent_of_code::template::runner::{parse_duration, RunOptions};
     use advent_of_code::template::Day;
     use std::process;
 
@@ -25,15 +29,30 @@ mod args {
             day: Day,
             release: bool,
             dhat: bool,
-            submit: Option<u8>,
+            spans: bool,
+            options: RunOptions,
         },
         All {
             release: bool,
+            options: RunOptions,
         },
         Time {
             all: bool,
             day: Option<Day>,
             store: bool,
+            allow_mixed: bool,
+            options: RunOptions,
+        },
+        TimeBisect {
+            day: Day,
+            good: String,
+            bad: String,
+            limit: Limit,
+            options: RunOptions,
+        },
+        Verify {
+            day: Option<Day>,
+            release: bool,
         },
         #[cfg(feature = "today")]
         Today,
@@ -45,17 +64,49 @@ mod args {
         let app_args = match args.subcommand()?.as_deref() {
             Some("all") => AppArguments::All {
                 release: args.contains("--release"),
+                options: RunOptions::from_args(&mut args)?,
             },
             Some("time") => {
                 let all = args.contains("--all");
                 let store = args.contains("--store");
+                let allow_mixed = args.contains("--allow-mixed");
+                let options = RunOptions::from_args(&mut args)?;
 
                 AppArguments::Time {
                     all,
                     day: args.opt_free_from_str()?,
                     store,
+                    allow_mixed,
+                    options,
+                }
+            }
+            Some("time-bisect") => {
+                let good = args.value_from_str("--good")?;
+                let bad = args.value_from_str("--bad")?;
+                let threshold = args.opt_value_from_fn("--threshold", parse_duration)?;
+                let ratio = args.opt_value_from_str("--ratio")?;
+                let limit = match (threshold, ratio) {
+                    (Some(_), Some(_)) => {
+                        eprintln!("Only one of `--threshold` and `--ratio` may be passed.");
+                        process::exit(1);
+                    }
+                    (Some(threshold), None) => Limit::Threshold(threshold),
+                    (None, ratio) => Limit::Ratio(ratio.unwrap_or(1.5)),
+                };
+                let options = RunOptions::from_args(&mut args)?;
+
+                AppArguments::TimeBisect {
+                    good,
+                    bad,
+                    limit,
+                    options,
+                    day: args.free_from_str()?,
                 }
             }
+            Some("verify") => AppArguments::Verify {
+                release: args.contains("--release"),
+                day: args.opt_free_from_str()?,
+            },
             Some("download") => AppArguments::Download {
                 day: args.free_from_str()?,
             },
@@ -67,10 +118,11 @@ mod args {
                 download: args.contains("--download"),
             },
             Some("solve") => AppArguments::Solve {
-                day: args.free_from_str()?,
                 release: args.contains("--release"),
-                submit: args.opt_value_from_str("--submit")?,
                 dhat: args.contains("--dhat"),
+                spans: args.contains("--spans"),
+                options: RunOptions::from_args(&mut args)?,
+                day: args.free_from_str()?,
             },
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
@@ -100,8 +152,22 @@ fn main() {
             std::process::exit(1);
         }
         Ok(args) => match args {
-            AppArguments::All { release } => all::handle(release),
-            AppArguments::Time { day, all, store } => time::handle(day, all, store),
+            AppArguments::All { release, options } => all::handle(release, &options),
+            AppArguments::Time {
+                day,
+                all,
+                store,
+                allow_mixed,
+                options,
+            } => time::handle(day, all, store, allow_mixed, &options),
+            AppArguments::TimeBisect {
+                day,
+                good,
+                bad,
+                limit,
+                options,
+            } => time_bisect::handle(day, &good, &bad, limit, &options),
+            AppArguments::Verify { day, release } => verify::handle(day, release),
             AppArguments::Download { day } => download::handle(day),
             AppArguments::Read { day } => read::handle(day),
             AppArguments::Scaffold { day, download } => {
@@ -114,8 +180,9 @@ fn main() {
                 day,
                 release,
                 dhat,
-                submit,
-            } => solve::handle(day, release, dhat, submit),
+                spans,
+                options,
+            } => solve::handle(day, release, dhat, spans, &options),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
@@ -0,0 +1,190 @@
+//! Parallel mining of MD5 digests of `salt + counter`.
+//!
+//! A [`Miner`] hashes consecutive counters on all cores and hands out the digests that pass a
+//! predicate in counter order. The predicates the puzzles need work on raw digests, which avoids
+//! formatting every digest as hex. Hashing itself is shared with the `md5s` precomputation tool.
+//!
+//! ```
+//! use advent_of_code::mining::{leading_zero_nibbles, nibble, Miner};
+//!
+//! let miner = Miner::new("abc");
+//! let (counter, digit) = miner
+//!     .hits(0, |_, d| (leading_zero_nibbles(d) >= 5).then(|| nibble(d, 5)))
+//!     .next()
+//!     .unwrap();
+//! assert_eq!((counter, digit), (3231929, 1));
+//! ```
+
+use std::{collections::VecDeque, thread};
+
+use md5::{Digest as _, Md5};
+pub use md5s::{from_hex, hash, to_hex, Digest};
+
+/// The MD5 digest of `data`.
+pub fn digest(data: &[u8]) -> Digest {
+    Md5::digest(data).into()
+}
+
+/// The `i`th hex digit of a digest, from the left.
+pub fn nibble(digest: &Digest, i: usize) -> u8 {
+    let byte = digest[i / 2];
+    if i.is_multiple_of(2) {
+        byte >> 4
+    } else {
+        byte & 0xf
+    }
+}
+
+pub fn leading_zero_nibbles(digest: &Digest) -> u32 {
+    let zeros = u128::from_be_bytes(*digest).leading_zeros();
+    zeros / 4
+}
+
+/// The hex digit of the first run of three equal digits.
+pub fn first_triple(digest: &Digest) -> Option<u8> {
+    (0..30)
+        .map(|i| nibble(digest, i))
+        .enumerate()
+        .find_map(|(i, n)| (n == nibble(digest, i + 1) && n == nibble(digest, i + 2)).then_some(n))
+}
+
+/// A bit set of the hex digits that appear five times in a row.
+pub fn quintuples(digest: &Digest) -> u16 {
+    let mut out = 0;
+    let mut run = 0;
+    for i in 0..32 {
+        let n = nibble(digest, i);
+        run = if i > 0 && n == nibble(digest, i - 1) {
+            run + 1
+        } else {
+            1
+        };
+        if run == 5 {
+            out |= 1 << n;
+        }
+    }
+    out
+}
+
+/// Hashes `salt + counter` for consecutive counters on several threads.
+#[derive(Clone, Debug)]
+pub struct Miner {
+    salt: Vec<u8>,
+    stretch: u32,
+    threads: usize,
+    batch: u64,
+}
+
+impl Miner {
+    pub fn new(salt: &str) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            salt: salt.as_bytes().to_vec(),
+            stretch: 0,
+            threads,
+            batch: 1 << 14,
+        }
+    }
+
+    /// Re-hashes every digest `stretch` times, see [`hash`].
+    pub fn stretch(mut self, stretch: u32) -> Self {
+        self.stretch = stretch;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// The number of counters each thread hashes before the hits are handed out. Smaller batches
+    /// waste less work when only a few hits are needed.
+    pub fn batch(mut self, batch: u64) -> Self {
+        self.batch = batch.max(1);
+        self
+    }
+
+    /// The digest of a single counter.
+    pub fn hash(&self, counter: u64) -> Digest {
+        hash(&self.salt, counter, self.stretch)
+    }
+
+    /// Mines the counters in `start..end` and returns the values `filter` produced, in order.
+    pub fn mine<T: Send>(
+        &self,
+        start: u64,
+        end: u64,
+        filter: &(impl Fn(u64, &Digest) -> Option<T> + Sync),
+    ) -> Vec<(u64, T)> {
+        md5s::mine(&self.salt, self.stretch, start, end, self.threads, filter)
+    }
+
+    /// An endless iterator over the hits from `start` on, in counter order.
+    pub fn hits<T: Send, F: Fn(u64, &Digest) -> Option<T> + Sync>(
+        &self,
+        start: u64,
+        filter: F,
+    ) -> Hits<'_, T, F> {
+        Hits {
+            miner: self,
+            filter,
+            next: start,
+            found: VecDeque::new(),
+        }
+    }
+}
+
+/// The iterator returned by [`Miner::hits`].
+pub struct Hits<'a, T, F> {
+    miner: &'a Miner,
+    filter: F,
+    next: u64,
+    found: VecDeque<(u64, T)>,
+}
+
+impl<T: Send, F: Fn(u64, &Digest) -> Option<T> + Sync> Iterator for Hits<'_, T, F> {
+    type Item = (u64, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.found.is_empty() {
+            let end = self.next + self.miner.batch * self.miner.threads as u64;
+            self.found
+                .extend(self.miner.mine(self.next, end, &self.filter));
+            self.next = end;
+        }
+        self.found.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inspects_digests() {
+        let d = from_hex("000001f2ccc4eeeee7000000000000ff").unwrap();
+        assert_eq!(leading_zero_nibbles(&d), 5);
+        assert_eq!((nibble(&d, 5), nibble(&d, 6)), (1, 0xf));
+        assert_eq!(first_triple(&d), Some(0));
+        assert_eq!(quintuples(&d), 1 << 0 | 1 << 0xe);
+        assert_eq!(
+            first_triple(&from_hex("0123456789abcdef0123456789abcdef").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn hands_out_hits_in_order() {
+        let filter = |_, d: &Digest| first_triple(d);
+        let serial: Vec<_> = (0..5000)
+            .filter_map(|i| filter(i, &hash(b"abc", i, 0)).map(|t| (i, t)))
+            .collect();
+        let miner = Miner::new("abc").threads(3).batch(100);
+        let hits: Vec<_> = miner
+            .hits(0, filter)
+            .take_while(|&(i, _)| i < 5000)
+            .collect();
+        assert_eq!(hits, serial);
+        assert_eq!(hits[0].0, 18);
+    }
+}
@@ -0,0 +1,364 @@
+//! Helpers for picking puzzle input apart.
+//!
+//! [`Pattern`]s extract the fields of lines that follow a fixed template, [`words`] those of
+//! whitespace-separated lines, and both convert them into tuples or arrays through
+//! [`FromFields`]. [`ints`] scans a line for all its integers and [`blocks`] splits the input at
+//! blank lines. Every failure is a [`ParseError`] pointing at the offending text.
+//!
+//! ```ignore
+//! const DISC: Pattern = Pattern::new("Disc #{} has {} positions; at time=0, it is at position {}.");
+//! let discs: Vec<(u32, i64, i64)> = DISC.parse_lines(input)?;
+//! ```
+
+use std::{any, str::FromStr};
+
+pub use crate::error::{map_lines, parse_in, parse_lines, parse_separated, ParseError};
+
+/// A line template in which every `{}` stands for a field.
+///
+/// Whitespace in the template matches any non-empty run of whitespace, so columns padded for
+/// alignment need no special care. A field extends up to the text that follows it in the
+/// template, or to the end of the line for a trailing field. Two fields may not be adjacent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pattern(&'static str);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Piece<'a> {
+    Literal(&'a str),
+    Space,
+    Field,
+}
+
+/// Splits a template into literals, whitespace and fields.
+fn pieces(mut template: &str) -> impl Iterator<Item = Piece<'_>> {
+    std::iter::from_fn(move || {
+        if template.is_empty() {
+            None
+        } else if let Some(rest) = template.strip_prefix("{}") {
+            template = rest;
+            Some(Piece::Field)
+        } else if template.starts_with(char::is_whitespace) {
+            template = template.trim_start();
+            Some(Piece::Space)
+        } else {
+            let end = template
+                .find(|c: char| c.is_whitespace())
+                .into_iter()
+                .chain(template.find("{}"))
+                .min()
+                .unwrap_or(template.len());
+            let (literal, rest) = template.split_at(end);
+            template = rest;
+            Some(Piece::Literal(literal))
+        }
+    })
+}
+
+/// The first word of `s`, used to show what was found instead of the expected text.
+fn first_word(s: &str) -> &str {
+    s.split_whitespace().next().unwrap_or(&s[s.len()..])
+}
+
+impl Pattern {
+    pub const fn new(template: &'static str) -> Self {
+        Self(template)
+    }
+
+    /// Returns the text of every field of `line`, in order.
+    ///
+    /// # Panics
+    ///
+    /// If the template contains two adjacent fields.
+    pub fn fields<'a>(&self, line: &'a str) -> Result<Vec<&'a str>, ParseError> {
+        let mut rest = line.trim_end();
+        let mut fields = vec![];
+        let mut pieces = pieces(self.0).peekable();
+
+        while let Some(piece) = pieces.next() {
+            match piece {
+                Piece::Literal(literal) => {
+                    rest = rest.strip_prefix(literal).ok_or_else(|| {
+                        let found = first_word(rest);
+                        ParseError::expected(format!("`{literal}`"), found).within(line, found)
+                    })?;
+                }
+                Piece::Space => {
+                    let trimmed = rest.trim_start();
+                    if trimmed.len() == rest.len() {
+                        let found = first_word(rest);
+                        return Err(ParseError::expected("whitespace", found).within(line, found));
+                    }
+                    rest = trimmed;
+                }
+                Piece::Field => {
+                    let end = match pieces.peek() {
+                        None => Some(rest.len()),
+                        Some(Piece::Space) => rest.find(char::is_whitespace),
+                        Some(Piece::Literal(literal)) => rest.find(literal),
+                        Some(Piece::Field) => {
+                            panic!("pattern `{}` has two adjacent fields", self.0)
+                        }
+                    };
+                    // without the following text, the field takes the rest and the next piece
+                    // reports what is missing.
+                    let (field, after) = rest.split_at(end.unwrap_or(rest.len()));
+                    fields.push(field);
+                    rest = after;
+                }
+            }
+        }
+
+        match rest.trim_start() {
+            "" => Ok(fields),
+            rest => Err(ParseError::expected("the end of the line", rest).within(line, rest)),
+        }
+    }
+
+    /// Parses the fields of `line` into `T`, usually a tuple.
+    ///
+    /// # Panics
+    ///
+    /// If `T` does not take as many fields as the template has.
+    pub fn parse<T: FromFields>(&self, line: &str) -> Result<T, ParseError> {
+        let fields = self.fields(line)?;
+        assert_eq!(
+            fields.len(),
+            T::COUNT,
+            "pattern `{}` does not match the number of fields requested",
+            self.0
+        );
+        T::from_fields(line, &fields)
+    }
+
+    /// Parses every non-blank line of `input`.
+    pub fn parse_lines<T: FromFields>(&self, input: &str) -> Result<Vec<T>, ParseError> {
+        map_lines(input, |line| self.parse(line)).collect()
+    }
+}
+
+/// Types that can be built from the fields of a line.
+pub trait FromFields: Sized {
+    /// The number of fields taken.
+    const COUNT: usize;
+
+    /// Builds the value from exactly [`Self::COUNT`] fields, all of which are part of `line`.
+    fn from_fields(line: &str, fields: &[&str]) -> Result<Self, ParseError>;
+}
+
+/// Parses a single field. Errors of the standard library's parsers are replaced by one naming the
+/// expected type.
+fn field<T>(line: &str, field: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: Into<ParseError>,
+{
+    field.parse().map_err(|e: T::Err| {
+        let name = any::type_name::<T>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default();
+        e.into()
+            .or_expected(format!("a valid `{name}`"), field)
+            .within(line, field)
+    })
+}
+
+macro_rules! tuple_fields {
+    ($count:literal: $($t:ident $i:tt),+) => {
+        impl<$($t),+> FromFields for ($($t,)+)
+        where
+            $($t: FromStr, $t::Err: Into<ParseError>,)+
+        {
+            const COUNT: usize = $count;
+
+            fn from_fields(line: &str, fields: &[&str]) -> Result<Self, ParseError> {
+                Ok(($(field::<$t>(line, fields[$i])?,)+))
+            }
+        }
+    };
+}
+
+tuple_fields!(1: A 0);
+tuple_fields!(2: A 0, B 1);
+tuple_fields!(3: A 0, B 1, C 2);
+tuple_fields!(4: A 0, B 1, C 2, D 3);
+tuple_fields!(5: A 0, B 1, C 2, D 3, E 4);
+tuple_fields!(6: A 0, B 1, C 2, D 3, E 4, F 5);
+
+impl<T, const N: usize> FromFields for [T; N]
+where
+    T: FromStr,
+    T::Err: Into<ParseError>,
+{
+    const COUNT: usize = N;
+
+    fn from_fields(line: &str, fields: &[&str]) -> Result<Self, ParseError> {
+        let values = fields
+            .iter()
+            .map(|f| field(line, f))
+            .collect::<Result<Vec<T>, _>>()?;
+        Ok(values
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("{N} fields were parsed")))
+    }
+}
+
+/// Parses the whitespace-separated words of `line` into `T`, which must take all of them.
+pub fn words<T: FromFields>(line: &str) -> Result<T, ParseError> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.len() != T::COUNT {
+        return Err(
+            ParseError::expected(format!("{} words", T::COUNT), line.trim())
+                .within(line, line.trim()),
+        );
+    }
+    T::from_fields(line, &words)
+}
+
+/// Parses every integer in `s`, in order.
+///
+/// A `-` counts as a sign only when it does not follow a letter or digit, so ranges like `5-8`
+/// yield `5` and `8`.
+pub fn ints<T>(s: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: Into<ParseError>,
+{
+    let bytes = s.as_bytes();
+    let mut numbers = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let signed = bytes[i] == b'-'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+            && (i == 0 || !bytes[i - 1].is_ascii_alphanumeric());
+        if signed || bytes[i].is_ascii_digit() {
+            let start = i;
+            i += 1;
+            while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+                i += 1;
+            }
+            numbers.push(field(s, &s[start..i])?);
+        } else {
+            i += 1;
+        }
+    }
+    Ok(numbers)
+}
+
+/// Splits `input` into blocks of lines separated by blank lines. Every block is paired with the
+/// 1-based number of its first line.
+pub fn blocks(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut lines = input.lines().enumerate().peekable();
+    std::iter::from_fn(move || {
+        while lines.next_if(|(_, l)| l.trim().is_empty()).is_some() {}
+        let (first, start) = lines.next()?;
+        let mut end = start;
+        while let Some((_, line)) = lines.next_if(|(_, l)| !l.trim().is_empty()) {
+            end = line;
+        }
+        let from = start.as_ptr() as usize - input.as_ptr() as usize;
+        let to = end.as_ptr() as usize - input.as_ptr() as usize + end.len();
+        Some((first + 1, &input[from..to]))
+    })
+}
+
+/// Applies `f` to every block of `input`, attaching the line an error points into.
+pub fn map_blocks<'a, T, E: Into<ParseError>>(
+    input: &'a str,
+    mut f: impl FnMut(&'a str) -> Result<T, E> + 'a,
+) -> impl Iterator<Item = Result<T, ParseError>> + 'a {
+    blocks(input).map(move |(line, block)| f(block).map_err(|e| e.into().in_block(line, block)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blocks, ints, map_blocks, words, ParseError, Pattern};
+
+    const DISC: Pattern =
+        Pattern::new("Disc #{} has {} positions; at time=0, it is at position {}.");
+    const NODE: Pattern = Pattern::new("/dev/grid/node-x{}-y{} {}T {}T {}T {}%");
+
+    #[test]
+    fn extracts_fields() {
+        let line = "Disc #1 has 13 positions; at time=0, it is at position 11.";
+        assert_eq!(DISC.fields(line).unwrap(), vec!["1", "13", "11"]);
+        assert_eq!(DISC.parse::<(u8, i64, i64)>(line).unwrap(), (1, 13, 11));
+
+        let line = "/dev/grid/node-x10-y2   92T   68T    24T   73%";
+        assert_eq!(
+            NODE.parse::<[u32; 6]>(line).unwrap(),
+            [10, 2, 92, 68, 24, 73]
+        );
+
+        let swap = Pattern::new("swap letter {} with letter {}");
+        assert_eq!(
+            swap.parse::<(char, char)>("swap letter d with letter b"),
+            Ok(('d', 'b'))
+        );
+    }
+
+    #[test]
+    fn reports_mismatches() {
+        let err = DISC
+            .parse_lines::<(u8, i64, i64)>(
+                "Disc #1 has 5 positions; at time=0, it is at position 4.\n\
+                 Disc #2 has two positions; at time=0, it is at position 1.",
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 13: expected a valid `i64`, found `two`"
+        );
+
+        let err = DISC.fields("Disc #1 holds 5 positions").unwrap_err();
+        assert_eq!(err.to_string(), "column 9: expected `has`, found `holds`");
+
+        let err = DISC
+            .fields("Disc #1 has 5 positions; at time=0, it is at position 4. Done")
+            .unwrap_err();
+        assert_eq!(err.column(), Some(58));
+        assert_eq!(err.found_text(), Some("Done"));
+
+        let err = DISC.fields("Disc #1 has 5 positions; at").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column 28: expected whitespace, found nothing"
+        );
+    }
+
+    #[test]
+    fn splits_words() {
+        assert_eq!(words::<[u32; 3]>("  5  10   25"), Ok([5, 10, 25]));
+        let err = words::<[u32; 3]>("5 10").unwrap_err();
+        assert_eq!(err.to_string(), "column 1: expected 3 words, found `5 10`");
+    }
+
+    #[test]
+    fn scans_integers() {
+        assert_eq!(ints::<i64>("x=-3, y=14..-2"), Ok(vec![-3, 14, -2]));
+        assert_eq!(ints::<u32>("5-8"), Ok(vec![5, 8]));
+        assert_eq!(ints::<u32>("node-x0-y12"), Ok(vec![0, 12]));
+        assert!(ints::<u8>("at 300").is_err());
+    }
+
+    #[test]
+    fn splits_blocks() {
+        let input = "a\nb\n\n\nc\n\nd\ne\n";
+        let found: Vec<_> = blocks(input).collect();
+        assert_eq!(found, vec![(1, "a\nb"), (5, "c"), (7, "d\ne")]);
+
+        let err = map_blocks(input, |block| {
+            block
+                .find('e')
+                .map(|i| Err(ParseError::new("no e allowed").within(block, &block[i..=i])))
+                .unwrap_or(Ok(()))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+        assert_eq!(err.to_string(), "line 8, column 1: no e allowed");
+        assert_eq!(
+            format!("{err:#}"),
+            "line 8, column 1: no e allowed\n  |\n8 | e\n  | ^"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! A small, seedable pseudo-random number generator.
+//!
+//! [`Lcg`] is a 64-bit linear congruential generator with Knuth's MMIX constants. It is neither
+//! fast nor good, but reproducible without dependencies, which is all that generated fixtures,
+//! fuzzing and visual noise need.
+
+/// A linear congruential generator; the same seed always gives the same numbers.
+#[derive(Debug, Clone, Default)]
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next 31 random bits.
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        // the low bits of an LCG have short periods, so only the high ones are used.
+        (self.state >> 33) as u32
+    }
+
+    /// A random number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        u64::from(self.next_u32()) % n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lcg;
+
+    #[test]
+    fn repeats_for_the_same_seed() {
+        let numbers = |seed| {
+            let mut lcg = Lcg::new(seed);
+            (0..100).map(|_| lcg.below(10)).collect::<Vec<_>>()
+        };
+        assert_eq!(numbers(7), numbers(7));
+        assert_ne!(numbers(7), numbers(8));
+        assert!(numbers(7).iter().all(|&n| n < 10));
+    }
+}
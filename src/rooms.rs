@@ -0,0 +1,290 @@
+//! The encrypted room list of day 4.
+//!
+//! A [`Room`] line such as `aaaaa-bbb-z-y-x-123[abxyz]` holds a name with its letters shifted by
+//! the sector ID, followed by a checksum of the name's five most common letters. Rooms whose
+//! checksum does not match are decoys. [`search`] lists the real rooms whose decrypted name
+//! matches a [`Query`], and [`Generator`] produces valid room lines for fixtures and fuzzing.
+//! Names whose sector ID is missing or wrong can still be decrypted with [`crack_name`].
+
+use std::{fmt::Display, str::FromStr};
+
+use regex::Regex;
+
+use crate::{
+    cipher::{self, Cracked, Shift},
+    error::{parse_in, ParseError},
+    random::Lcg,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Room {
+    /// The encrypted name, words separated by dashes.
+    pub name: String,
+    pub id: u32,
+    pub checksum: [char; 5],
+}
+
+impl FromStr for Room {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (beginning, rest) = s
+            .split_once('[')
+            .ok_or_else(|| ParseError::expected("a `[checksum]`", s).within(s, s))?;
+        let (name, id) = beginning.rsplit_once('-').ok_or_else(|| {
+            ParseError::expected("`<name>-<sector id>`", beginning).within(s, beginning)
+        })?;
+        if let Some(i) = name.find(|c: char| c != '-' && !c.is_ascii_lowercase()) {
+            let c = &name[i..i + name[i..].chars().next().map_or(1, char::len_utf8)];
+            return Err(ParseError::expected("a lowercase letter or `-`", c).within(s, c));
+        }
+        let id = parse_in(s, id, "a sector id")?;
+        let checksum = rest.strip_suffix(']').ok_or_else(|| {
+            ParseError::new("the checksum is missing its closing `]`").within(s, rest)
+        })?;
+        Ok(Self {
+            name: name.into(),
+            id,
+            checksum: parse_checksum(checksum).map_err(|e| e.within(s, checksum))?,
+        })
+    }
+}
+
+/// Checks that a checksum consists of five lowercase letters. Checksums with a repeated letter
+/// parse, but never match a name, so such rooms are decoys.
+fn parse_checksum(checksum: &str) -> Result<[char; 5], ParseError> {
+    for (i, c) in checksum.char_indices() {
+        let letter = &checksum[i..i + c.len_utf8()];
+        if !c.is_ascii_lowercase() {
+            return Err(ParseError::expected("a lowercase letter", letter).within(checksum, letter));
+        }
+    }
+    let letters: Vec<char> = checksum.chars().collect();
+    letters.try_into().map_err(|letters: Vec<char>| {
+        ParseError::new(format!(
+            "the checksum has {} letters instead of five",
+            letters.len()
+        ))
+        .within(checksum, checksum)
+    })
+}
+
+/// The up to five most common letters of an encrypted name, ties broken alphabetically.
+pub fn checksum(name: &str) -> Vec<char> {
+    let mut counts = [0u32; 26];
+    for c in name.chars().filter(char::is_ascii_lowercase) {
+        counts[(c as u8 - b'a') as usize] += 1;
+    }
+    let mut letters: Vec<char> = (b'a'..=b'z')
+        .map(char::from)
+        .filter(|c| counts[(*c as u8 - b'a') as usize] > 0)
+        .collect();
+    letters.sort_by_key(|c| std::cmp::Reverse(counts[(*c as u8 - b'a') as usize]));
+    letters.truncate(5);
+    letters
+}
+
+impl Room {
+    /// Whether the checksum matches the name, i.e. the room is real.
+    pub fn is_valid(&self) -> bool {
+        checksum(&self.name)
+            .into_iter()
+            .zip(self.checksum)
+            .all(|(c1, c2)| c1 == c2)
+    }
+
+    /// The shift that encrypted the name, i.e. moving every letter back by the sector ID.
+    pub fn shift(&self) -> Shift {
+        Shift::new(-i64::from(self.id))
+    }
+
+    /// The name decrypted with the sector ID, with dashes turned into spaces.
+    pub fn decrypt(&self) -> String {
+        self.shift().decrypt(&self.name.replace('-', " "))
+    }
+
+    /// Decrypts the name by letter frequencies, for rooms whose sector ID is missing or wrong.
+    pub fn crack(&self) -> Cracked {
+        crack_name(&self.name)
+    }
+}
+
+/// Decrypts an encrypted room name without knowing its sector ID.
+pub fn crack_name(name: &str) -> Cracked {
+    cipher::crack(&name.replace('-', " "))
+}
+
+/// Prints the room as a line of the room list.
+impl Display for Room {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let checksum: String = self.checksum.iter().collect();
+        write!(f, "{}-{}[{checksum}]", self.name, self.id)
+    }
+}
+
+/// What to look for in decrypted room names.
+#[derive(Debug, Clone)]
+pub enum Query {
+    Keyword(String),
+    Regex(Regex),
+}
+
+impl Query {
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Query::Keyword(keyword) => name.contains(keyword.as_str()),
+            Query::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// All real rooms whose decrypted name matches `query`, along with that name.
+pub fn search<'a>(
+    rooms: &'a [Room],
+    query: &'a Query,
+) -> impl Iterator<Item = (&'a Room, String)> + 'a {
+    rooms
+        .iter()
+        .filter(|room| room.is_valid())
+        .map(|room| (room, room.decrypt()))
+        .filter(|(_, name)| query.matches(name))
+}
+
+/// Produces an endless, reproducible stream of valid room lines.
+pub struct Generator {
+    random: Lcg,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            random: Lcg::new(seed),
+        }
+    }
+
+    fn random(&mut self, n: u64) -> u64 {
+        self.random.below(n)
+    }
+}
+
+impl Iterator for Generator {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let words: Vec<String> = (0..1 + self.random(5))
+                .map(|_| {
+                    (0..1 + self.random(10))
+                        .map(|_| char::from(b'a' + self.random(26) as u8))
+                        .collect()
+                })
+                .collect();
+            let name = words.join("-");
+            // a checksum needs five different letters.
+            let Ok(checksum) = checksum(&name).try_into() else {
+                continue;
+            };
+            let id = 100 + self.random(900) as u32;
+            return Some(Room { name, id, checksum }.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::{crack_name, search, Generator, Query, Room};
+
+    #[test]
+    fn checks_rooms() {
+        let rooms = [
+            "aaaaa-bbb-z-y-x-123[abxyz]",
+            "a-b-c-d-e-f-g-h-987[abcde]",
+            "not-a-real-room-404[oarel]",
+            "totally-real-room-200[decoy]",
+            "aaaaa-bbb-z-y-x-123[aabxy]",
+        ]
+        .map(|line| line.parse::<Room>().unwrap());
+        let valid: Vec<bool> = rooms.iter().map(Room::is_valid).collect();
+        assert_eq!(valid, [true, true, true, false, false]);
+        assert_eq!(rooms[0].to_string(), "aaaaa-bbb-z-y-x-123[abxyz]");
+
+        let room: Room = "qzmt-zixmtkozy-ivhz-343[zimth]".parse().unwrap();
+        assert_eq!(room.decrypt(), "very encrypted name");
+        assert_eq!(room.shift().key(), 21);
+    }
+
+    #[test]
+    fn cracks_names_without_sector_ids() {
+        let room: Room = "kloqemlib-lygbzq-pqloxdb-341[lbqod]".parse().unwrap();
+        let cracked = room.crack();
+        assert_eq!(cracked.plaintext, "northpole object storage");
+        assert_eq!(cracked.shift, room.shift());
+        assert!(cracked.confidence > 0.5, "{}", cracked.confidence);
+
+        // the sector ID is wrong, but the name still decrypts.
+        let room = Room { id: 1, ..room };
+        assert_ne!(room.decrypt(), "northpole object storage");
+        assert_eq!(room.crack().plaintext, "northpole object storage");
+        assert_eq!(
+            crack_name("sbov-bkzovmqba-kxjb").plaintext,
+            "very encrypted name"
+        );
+    }
+
+    #[test]
+    fn explains_malformed_checksums() {
+        let error = |line: &str| line.parse::<Room>().unwrap_err().to_string();
+        assert_eq!(
+            error("abc-1[abcd]"),
+            "column 7: the checksum has 4 letters instead of five"
+        );
+        assert_eq!(
+            error("abc-1[abCde]"),
+            "column 9: expected a lowercase letter, found `C`"
+        );
+        assert_eq!(
+            error("abc-1[abcde"),
+            "column 7: the checksum is missing its closing `]`"
+        );
+        assert_eq!(
+            error("ab_c-1[abcde]"),
+            "column 3: expected a lowercase letter or `-`, found `_`"
+        );
+    }
+
+    #[test]
+    fn searches_decrypted_names() {
+        let rooms: Vec<Room> = [
+            "qzmt-zixmtkozy-ivhz-343[zimth]",
+            "a-b-c-d-e-f-g-h-987[abcde]",
+        ]
+        .iter()
+        .map(|line| line.parse().unwrap())
+        .collect();
+        let ids = |query| {
+            search(&rooms, &query)
+                .map(|(r, _)| r.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(Query::Keyword("crypt".into())), [343]);
+        assert_eq!(ids(Query::Keyword("z a b".into())), [987]);
+        assert_eq!(
+            ids(Query::Regex(Regex::new("^(very|z) ").unwrap())),
+            [343, 987]
+        );
+        assert!(ids(Query::Keyword("north".into())).is_empty());
+    }
+
+    #[test]
+    fn generates_valid_rooms() {
+        let lines: Vec<String> = Generator::new(4).take(200).collect();
+        for line in &lines {
+            let room: Room = line.parse().unwrap();
+            assert!(room.is_valid(), "{line}");
+            assert_eq!(&room.to_string(), line);
+        }
+        assert_eq!(lines, Generator::new(4).take(200).collect::<Vec<_>>());
+    }
+}
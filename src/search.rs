@@ -0,0 +1,433 @@
+//! Graph searches over implicit graphs.
+//!
+//! A graph is given by its start state and a function returning the neighbours of a state.
+//! Searches are started from a [`Search`], which decides whether the path to the goal is
+//! recorded, and return an [`Outcome`] that also tells how many states were expanded.
+//!
+//! ```
+//! use advent_of_code::search::Search;
+//!
+//! let outcome = Search::new(1).with_paths().bfs(|&n| [n + 1, n * 2], |&n| n == 10);
+//! assert_eq!(outcome.cost(), Some(4));
+//! assert_eq!(outcome.path, Some(vec![1, 2, 4, 5, 10]));
+//! ```
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// The cost of an edge. `Default` has to be zero.
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<C: Copy + Ord + Add<Output = C> + Default> Cost for C {}
+
+/// The result of a search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome<S, C> {
+    /// The goal that was reached and the cost of the cheapest path to it.
+    pub found: Option<(S, C)>,
+    /// The states from the start up to and including the goal, if paths are recorded.
+    pub path: Option<Vec<S>>,
+    /// The number of states whose neighbours were generated.
+    pub expanded: usize,
+}
+
+impl<S, C: Copy> Outcome<S, C> {
+    pub fn cost(&self) -> Option<C> {
+        self.found.as_ref().map(|&(_, cost)| cost)
+    }
+}
+
+/// The states found by [`Search::reachable`].
+#[derive(Clone, Debug)]
+pub struct Reached<S> {
+    /// Every reached state with the number of steps it takes to get there.
+    pub depths: HashMap<S, usize>,
+    pub expanded: usize,
+}
+
+/// A state in the priority queue, ordered so that the lowest priority is popped first.
+struct Queued<S, C> {
+    priority: C,
+    cost: C,
+    state: S,
+}
+
+impl<S, C: Ord> PartialEq for Queued<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S, C: Ord> Eq for Queued<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Queued<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Queued<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // among equal priorities, prefer the states that got further.
+        (Reverse(&self.priority), &self.cost).cmp(&(Reverse(&other.priority), &other.cost))
+    }
+}
+
+/// A search from a start state.
+#[derive(Clone, Debug)]
+pub struct Search<S> {
+    start: S,
+    paths: bool,
+}
+
+impl<S: Clone + Eq + Hash> Search<S> {
+    pub fn new(start: S) -> Self {
+        Self {
+            start,
+            paths: false,
+        }
+    }
+
+    /// Records the path to the goal, at the cost of remembering the parent of every state.
+    pub fn with_paths(mut self) -> Self {
+        self.paths = true;
+        self
+    }
+
+    fn path(&self, parents: &HashMap<S, S>, goal: &S) -> Option<Vec<S>> {
+        if !self.paths {
+            return None;
+        }
+        let mut path = vec![goal.clone()];
+        while let Some(parent) = parents.get(path.last()?) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Breadth-first search for the goal with the fewest steps.
+    pub fn bfs<I: IntoIterator<Item = S>>(
+        self,
+        mut neighbours: impl FnMut(&S) -> I,
+        mut is_goal: impl FnMut(&S) -> bool,
+    ) -> Outcome<S, usize> {
+        let mut seen = HashSet::from([self.start.clone()]);
+        let mut parents = HashMap::new();
+        let mut queue = VecDeque::from([(self.start.clone(), 0)]);
+        let mut expanded = 0;
+
+        while let Some((state, steps)) = queue.pop_front() {
+            if is_goal(&state) {
+                return Outcome {
+                    path: self.path(&parents, &state),
+                    found: Some((state, steps)),
+                    expanded,
+                };
+            }
+            expanded += 1;
+            for next in neighbours(&state) {
+                if seen.insert(next.clone()) {
+                    if self.paths {
+                        parents.insert(next.clone(), state.clone());
+                    }
+                    queue.push_back((next, steps + 1));
+                }
+            }
+        }
+
+        Outcome {
+            found: None,
+            path: None,
+            expanded,
+        }
+    }
+
+    /// Dijkstra's algorithm for the cheapest goal on a graph with weighted edges.
+    pub fn dijkstra<C: Cost, I: IntoIterator<Item = (S, C)>>(
+        self,
+        neighbours: impl FnMut(&S) -> I,
+        is_goal: impl FnMut(&S) -> bool,
+    ) -> Outcome<S, C> {
+        self.a_star(neighbours, |_| C::default(), is_goal)
+    }
+
+    /// A* search. The heuristic must never overestimate the remaining cost, and the search
+    /// expands fewest states when it is also consistent along every edge.
+    pub fn a_star<C: Cost, I: IntoIterator<Item = (S, C)>>(
+        self,
+        mut neighbours: impl FnMut(&S) -> I,
+        mut heuristic: impl FnMut(&S) -> C,
+        mut is_goal: impl FnMut(&S) -> bool,
+    ) -> Outcome<S, C> {
+        let mut best = HashMap::from([(self.start.clone(), C::default())]);
+        let mut parents = HashMap::new();
+        let mut queue = BinaryHeap::from([Queued {
+            priority: heuristic(&self.start),
+            cost: C::default(),
+            state: self.start.clone(),
+        }]);
+        let mut expanded = 0;
+
+        while let Some(Queued { cost, state, .. }) = queue.pop() {
+            if best.get(&state).is_some_and(|&b| b < cost) {
+                // a cheaper way to this state was found after it was queued.
+                continue;
+            }
+            if is_goal(&state) {
+                return Outcome {
+                    path: self.path(&parents, &state),
+                    found: Some((state, cost)),
+                    expanded,
+                };
+            }
+            expanded += 1;
+            for (next, step) in neighbours(&state) {
+                let next_cost = cost + step;
+                match best.entry(next.clone()) {
+                    Entry::Occupied(e) if *e.get() <= next_cost => continue,
+                    Entry::Occupied(mut e) => {
+                        e.insert(next_cost);
+                    }
+                    Entry::Vacant(e) => {
+                        e.insert(next_cost);
+                    }
+                }
+                if self.paths {
+                    parents.insert(next.clone(), state.clone());
+                }
+                queue.push(Queued {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    state: next,
+                });
+            }
+        }
+
+        Outcome {
+            found: None,
+            path: None,
+            expanded,
+        }
+    }
+
+    /// Iterative deepening A*. Uses memory linear in the depth of the solution instead of
+    /// remembering every state, in exchange for expanding states again on every iteration.
+    pub fn ida_star<C: Cost, I: IntoIterator<Item = (S, C)>>(
+        self,
+        mut neighbours: impl FnMut(&S) -> I,
+        mut heuristic: impl FnMut(&S) -> C,
+        mut is_goal: impl FnMut(&S) -> bool,
+    ) -> Outcome<S, C> {
+        struct Dfs<'a, S, C, N, H, G> {
+            neighbours: &'a mut N,
+            heuristic: &'a mut H,
+            is_goal: &'a mut G,
+            path: Vec<S>,
+            expanded: usize,
+            _cost: std::marker::PhantomData<C>,
+        }
+
+        enum Step<C> {
+            Found(C),
+            /// The smallest estimate that exceeded the bound, if any.
+            Exceeded(Option<C>),
+        }
+
+        impl<S, C, N, H, G, I> Dfs<'_, S, C, N, H, G>
+        where
+            S: Clone + Eq,
+            C: Cost,
+            N: FnMut(&S) -> I,
+            H: FnMut(&S) -> C,
+            G: FnMut(&S) -> bool,
+            I: IntoIterator<Item = (S, C)>,
+        {
+            fn search(&mut self, cost: C, bound: C) -> Step<C> {
+                let state = self
+                    .path
+                    .last()
+                    .expect("the path starts at the start state");
+                let estimate = cost + (self.heuristic)(state);
+                if estimate > bound {
+                    return Step::Exceeded(Some(estimate));
+                }
+                if (self.is_goal)(state) {
+                    return Step::Found(cost);
+                }
+                self.expanded += 1;
+                let mut next_bound = None;
+                for (next, step) in (self.neighbours)(state) {
+                    if self.path.contains(&next) {
+                        continue;
+                    }
+                    self.path.push(next);
+                    match self.search(cost + step, bound) {
+                        Step::Found(cost) => return Step::Found(cost),
+                        Step::Exceeded(Some(b)) => {
+                            next_bound = Some(next_bound.map_or(b, |n: C| n.min(b)));
+                        }
+                        Step::Exceeded(None) => {}
+                    }
+                    self.path.pop();
+                }
+                Step::Exceeded(next_bound)
+            }
+        }
+
+        let mut bound = heuristic(&self.start);
+        let mut dfs = Dfs {
+            neighbours: &mut neighbours,
+            heuristic: &mut heuristic,
+            is_goal: &mut is_goal,
+            path: vec![self.start.clone()],
+            expanded: 0,
+            _cost: std::marker::PhantomData,
+        };
+        loop {
+            match dfs.search(C::default(), bound) {
+                Step::Found(cost) => {
+                    let goal = dfs.path.last().cloned().expect("the path is never empty");
+                    return Outcome {
+                        found: Some((goal, cost)),
+                        path: self.paths.then_some(dfs.path),
+                        expanded: dfs.expanded,
+                    };
+                }
+                Step::Exceeded(Some(next)) => bound = next,
+                Step::Exceeded(None) => {
+                    return Outcome {
+                        found: None,
+                        path: None,
+                        expanded: dfs.expanded,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every state that can be reached in at most `max_depth` steps.
+    pub fn reachable<I: IntoIterator<Item = S>>(
+        self,
+        mut neighbours: impl FnMut(&S) -> I,
+        max_depth: usize,
+    ) -> Reached<S> {
+        let mut depths = HashMap::from([(self.start.clone(), 0)]);
+        let mut queue = VecDeque::from([(self.start, 0)]);
+        let mut expanded = 0;
+
+        while let Some((state, depth)) = queue.pop_front() {
+            if depth == max_depth {
+                continue;
+            }
+            expanded += 1;
+            for next in neighbours(&state) {
+                if let Entry::Vacant(e) = depths.entry(next.clone()) {
+                    e.insert(depth + 1);
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+
+        Reached { depths, expanded }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Search;
+
+    /// A 6x6 grid with a wall in column 3 that is open in row 5.
+    fn grid_neighbours(&(x, y): &(i32, i32)) -> Vec<((i32, i32), u32)> {
+        [(0, 1), (1, 0), (0, -1), (-1, 0)]
+            .into_iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|&(x, y)| (0..6).contains(&x) && (0..6).contains(&y))
+            .filter(|&(x, y)| x != 3 || y == 5)
+            .map(|p| (p, 1))
+            .collect()
+    }
+
+    fn manhattan(&(x, y): &(i32, i32)) -> u32 {
+        x.abs_diff(5) + y.abs_diff(0)
+    }
+
+    fn is_goal(&p: &(i32, i32)) -> bool {
+        p == (5, 0)
+    }
+
+    #[test]
+    fn searches_agree_on_costs() {
+        let bfs =
+            Search::new((0, 0)).bfs(|p| grid_neighbours(p).into_iter().map(|(p, _)| p), is_goal);
+        let dijkstra = Search::new((0, 0)).dijkstra(grid_neighbours, is_goal);
+        let a_star = Search::new((0, 0)).a_star(grid_neighbours, manhattan, is_goal);
+        let ida_star = Search::new((0, 0)).ida_star(grid_neighbours, manhattan, is_goal);
+
+        assert_eq!(bfs.cost(), Some(15));
+        assert_eq!(dijkstra.cost(), Some(15));
+        assert_eq!(a_star.cost(), Some(15));
+        assert_eq!(ida_star.cost(), Some(15));
+        assert!(a_star.expanded < dijkstra.expanded);
+        assert!(bfs.path.is_none() && ida_star.path.is_none());
+    }
+
+    #[test]
+    fn reconstructs_paths() {
+        for outcome in [
+            Search::new((0, 0))
+                .with_paths()
+                .a_star(grid_neighbours, manhattan, is_goal),
+            Search::new((0, 0))
+                .with_paths()
+                .ida_star(grid_neighbours, manhattan, is_goal),
+        ] {
+            let path = outcome.path.unwrap();
+            assert_eq!(path.len(), 16);
+            assert_eq!((path[0], path[15]), ((0, 0), (5, 0)));
+            assert!(path.contains(&(3, 5)));
+            assert!(path
+                .windows(2)
+                .all(|w| grid_neighbours(&w[0]).iter().any(|&(p, _)| p == w[1])));
+        }
+    }
+
+    #[test]
+    fn prefers_cheaper_edges() {
+        // going through 1 costs 1 + 1, the direct edge costs 5.
+        let neighbours = |&n: &u8| match n {
+            0 => vec![(1, 1), (2, 5)],
+            1 => vec![(2, 1)],
+            _ => vec![],
+        };
+        let outcome = Search::new(0)
+            .with_paths()
+            .dijkstra(neighbours, |&n| n == 2);
+        assert_eq!(outcome.found, Some((2, 2)));
+        assert_eq!(outcome.path, Some(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn reports_unreachable_goals() {
+        let outcome = Search::new(0u8).bfs(|&n| (n < 5).then_some(n + 1), |&n| n == 9);
+        assert_eq!(outcome.found, None);
+        assert_eq!(outcome.expanded, 6);
+
+        let outcome =
+            Search::new(0u8).ida_star(|&n| (n < 5).then_some((n + 1, 1u32)), |_| 0, |&n| n == 9);
+        assert_eq!(outcome.found, None);
+    }
+
+    #[test]
+    fn bounds_reachability() {
+        let reached =
+            Search::new((0, 0)).reachable(|p| grid_neighbours(p).into_iter().map(|(p, _)| p), 2);
+        assert_eq!(reached.depths.len(), 6);
+        assert_eq!(reached.depths[&(1, 1)], 2);
+        assert_eq!(reached.expanded, 3);
+    }
+}
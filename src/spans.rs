@@ -0,0 +1,235 @@
+//! Hierarchical span timings for solutions.
+//!
+//! Wrap a region in [`span!`](crate::span) to record how long it took and how often it ran.
+//! Spans nest: a span entered while another one is active is recorded as its child.
+//! Recording only happens when the `spans` feature is enabled (`cargo solve <day> --spans`),
+//! otherwise the guards are zero-sized and compile away.
+
+#[cfg(feature = "spans")]
+use std::{cell::RefCell, time::Instant};
+use std::{fmt::Display, time::Duration};
+
+/// Times the rest of the enclosing block as a span with the given name.
+///
+/// ```
+/// fn search() {
+///     advent_of_code::span!("search");
+///     // ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! span {
+    ($name:expr) => {
+        let _span_guard = $crate::spans::enter($name);
+    };
+}
+
+/// A recorded span with its accumulated time and number of calls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub name: &'static str,
+    pub total: Duration,
+    pub calls: u64,
+    pub children: Vec<Span>,
+}
+
+/// The span trees recorded since the last call to [`take`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub spans: Vec<Span>,
+}
+
+impl Report {
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write_span(
+            f: &mut std::fmt::Formatter<'_>,
+            span: &Span,
+            depth: usize,
+        ) -> std::fmt::Result {
+            let indent = "  ".repeat(depth + 1);
+            let name_width = 24_usize.saturating_sub(indent.len());
+            let calls = if span.calls == 1 { "call" } else { "calls" };
+            writeln!(
+                f,
+                "{indent}{:<name_width$} {:>10} ({} {calls})",
+                span.name,
+                format!("{:.1?}", span.total),
+                span.calls
+            )?;
+            for child in &span.children {
+                write_span(f, child, depth + 1)?;
+            }
+            Ok(())
+        }
+
+        for span in &self.spans {
+            write_span(f, span, 0)?;
+        }
+        Ok(())
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "spans")]
+struct Node {
+    name: &'static str,
+    total: Duration,
+    calls: u64,
+    children: Vec<usize>,
+}
+
+#[cfg(feature = "spans")]
+#[derive(Default)]
+struct Recorder {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+    stack: Vec<usize>,
+}
+
+#[cfg(feature = "spans")]
+impl Recorder {
+    fn enter(&mut self, name: &'static str) {
+        let siblings = match self.stack.last() {
+            Some(&parent) => &self.nodes[parent].children,
+            None => &self.roots,
+        };
+
+        let index = match siblings.iter().find(|&&i| self.nodes[i].name == name) {
+            Some(&index) => index,
+            None => {
+                let index = self.nodes.len();
+                self.nodes.push(Node {
+                    name,
+                    total: Duration::ZERO,
+                    calls: 0,
+                    children: vec![],
+                });
+                match self.stack.last() {
+                    Some(&parent) => self.nodes[parent].children.push(index),
+                    None => self.roots.push(index),
+                }
+                index
+            }
+        };
+
+        self.nodes[index].calls += 1;
+        self.stack.push(index);
+    }
+
+    fn exit(&mut self, elapsed: Duration) {
+        if let Some(index) = self.stack.pop() {
+            self.nodes[index].total += elapsed;
+        }
+    }
+
+    fn build(&self, index: usize) -> Span {
+        let node = &self.nodes[index];
+        Span {
+            name: node.name,
+            total: node.total,
+            calls: node.calls,
+            children: node.children.iter().map(|&i| self.build(i)).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "spans")]
+thread_local! {
+    static RECORDER: RefCell<Recorder> = RefCell::new(Recorder::default());
+}
+
+/// Guard returned by [`enter`]. The span ends when it is dropped.
+#[cfg(feature = "spans")]
+pub struct SpanGuard {
+    start: Instant,
+}
+
+#[cfg(feature = "spans")]
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        RECORDER.with(|r| r.borrow_mut().exit(elapsed));
+    }
+}
+
+/// Starts a span. Prefer the [`span!`](crate::span) macro.
+#[cfg(feature = "spans")]
+#[must_use]
+pub fn enter(name: &'static str) -> SpanGuard {
+    RECORDER.with(|r| r.borrow_mut().enter(name));
+    SpanGuard {
+        start: Instant::now(),
+    }
+}
+
+/// Returns the spans recorded on the current thread and resets the recorder.
+#[cfg(feature = "spans")]
+pub fn take() -> Report {
+    RECORDER.with(|r| {
+        let recorder = r.take();
+        Report {
+            spans: recorder.roots.iter().map(|&i| recorder.build(i)).collect(),
+        }
+    })
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Guard returned by [`enter`]. Does nothing unless the `spans` feature is enabled.
+#[cfg(not(feature = "spans"))]
+pub struct SpanGuard;
+
+/// Starts a span. Prefer the [`span!`](crate::span) macro.
+#[cfg(not(feature = "spans"))]
+#[inline(always)]
+#[must_use]
+pub fn enter(_name: &'static str) -> SpanGuard {
+    SpanGuard
+}
+
+/// Returns the spans recorded on the current thread and resets the recorder.
+#[cfg(not(feature = "spans"))]
+#[inline(always)]
+pub fn take() -> Report {
+    Report::default()
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(all(test, feature = "spans"))]
+mod tests {
+    use super::take;
+
+    fn leaf() {
+        crate::span!("leaf");
+    }
+
+    #[test]
+    fn records_nested_spans() {
+        take();
+        {
+            crate::span!("outer");
+            leaf();
+            leaf();
+        }
+        leaf();
+
+        let report = take();
+        assert_eq!(report.spans.len(), 2);
+        assert_eq!(report.spans[0].name, "outer");
+        assert_eq!(report.spans[0].calls, 1);
+        assert_eq!(report.spans[0].children.len(), 1);
+        assert_eq!(report.spans[0].children[0].name, "leaf");
+        assert_eq!(report.spans[0].children[0].calls, 2);
+        assert_eq!(report.spans[1].name, "leaf");
+        assert_eq!(report.spans[1].calls, 1);
+        assert!(take().is_empty());
+    }
+}
@@ -1,5 +1,6 @@
+use crate::template::runner::RunOptions;
 use crate::template::{all_days, run_multi::run_multi};
 
-pub fn handle(is_release: bool) {
-    run_multi(&all_days().collect(), is_release, false);
+pub fn handle(is_release: bool, options: &RunOptions) {
+    run_multi(&all_days().collect(), is_release, options);
 }
@@ -4,3 +4,5 @@ pub mod read;
 pub mod scaffold;
 pub mod solve;
 pub mod time;
+pub mod time_bisect;
+pub mod verify;
@@ -1,10 +1,19 @@
 use std::collections::HashSet;
+use std::process;
 
-use crate::template::run_multi::run_multi;
+use crate::template::answers::Answers;
+use crate::template::run_multi::{child_commands, run_multi};
+use crate::template::runner::RunOptions;
 use crate::template::timings::Timings;
-use crate::template::{all_days, readme_benchmarks, Day};
+use crate::template::{all_days, readme_benchmarks, readme_stars, Day};
 
-pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
+pub fn handle(
+    day: Option<Day>,
+    run_all: bool,
+    store: bool,
+    allow_mixed: bool,
+    options: &RunOptions,
+) {
     let stored_timings = Timings::read_from_file();
 
     let days_to_run = day.map_or_else(
@@ -21,14 +30,29 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
         |day| HashSet::from([day]),
     );
 
-    let timings = run_multi(&days_to_run, true, true).unwrap();
+    let options = RunOptions {
+        time: true,
+        ..options.clone()
+    };
+
+    let mut timings = run_multi(&days_to_run, true, &options).unwrap();
+
+    if let Some(part) = options.part {
+        carry_over_other_part(&mut timings, &stored_timings, part);
+    }
 
     if store {
-        let merged_timings = stored_timings.merge(&timings);
+        let merged_timings = match stored_timings.merge(&timings, allow_mixed) {
+            Ok(merged_timings) => merged_timings,
+            Err(e) => {
+                eprintln!("Failed to store updated benchmarks: {e}");
+                process::exit(1);
+            }
+        };
         merged_timings.store_file().unwrap();
 
         println!();
-        match readme_benchmarks::update(merged_timings) {
+        match readme_benchmarks::update(merged_timings, allow_mixed) {
             Ok(()) => {
                 println!("Stored updated benchmarks.");
             }
@@ -36,5 +60,37 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
                 eprintln!("Failed to store updated benchmarks. {}", e);
             }
         }
+
+        match readme_stars::update(&Answers::read_from_file()) {
+            Ok(true) => {
+                println!("Stored updated stars.");
+            }
+            Ok(false) => {
+                println!("No accepted answers recorded, leaving the stars untouched.");
+            }
+            Err(e) => {
+                eprintln!("Failed to store updated stars. {}", e);
+            }
+        }
+    }
+}
+
+/// When only one part was benched, keep the stored timing of the other part.
+fn carry_over_other_part(timings: &mut Timings, stored_timings: &Timings, part: u8) {
+    for timing in &mut timings.data {
+        let Some(stored) = stored_timings.data.iter().find(|t| t.day == timing.day) else {
+            continue;
+        };
+
+        let (other, stored_other) = if part == 1 {
+            (&mut timing.part_2, &stored.part_2)
+        } else {
+            (&mut timing.part_1, &stored.part_1)
+        };
+
+        if let Some(stored_other) = stored_other {
+            timing.total_nanos += child_commands::parse_timing_str(stored_other).unwrap_or(0_f64);
+            *other = Some(stored_other.clone());
+        }
     }
 }
@@ -0,0 +1,257 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{stdout, Write},
+    path::PathBuf,
+    process::{self, Command, Stdio},
+    time::Duration,
+};
+
+use crate::template::run_multi::child_commands;
+use crate::template::runner::RunOptions;
+use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+static WORKTREE_PATH: &str = "target/time-bisect";
+
+/// Decides whether a timing counts as a regression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    /// Slower than this absolute duration.
+    Threshold(Duration),
+    /// Slower than the timing of the good revision multiplied by this factor.
+    Ratio(f64),
+}
+
+/// Binary-searches the commits between `good` and `bad` for the first one where a day got slower than `limit`.
+pub fn handle(day: Day, good: &str, bad: &str, limit: Limit, options: &RunOptions) {
+    // errors are returned up to here, so that the worktree is removed before exiting.
+    if let Err(e) = bisect(day, good, bad, limit, options) {
+        eprintln!("time-bisect: {e}");
+        process::exit(1);
+    }
+}
+
+fn bisect(
+    day: Day,
+    good: &str,
+    bad: &str,
+    limit: Limit,
+    options: &RunOptions,
+) -> Result<(), String> {
+    let options = RunOptions {
+        time: true,
+        submit: None,
+        ..options.clone()
+    };
+
+    let good = rev_parse(good)?;
+    let bad = rev_parse(bad)?;
+
+    let commits = git(&[
+        "rev-list",
+        "--ancestry-path",
+        "--reverse",
+        &format!("{good}..{bad}"),
+    ])?;
+
+    if commits.is_empty() {
+        return Err("no commits between the good and the bad revision.".into());
+    }
+
+    // the good revision followed by every commit up to and including the bad one.
+    let revs: Vec<&str> = std::iter::once(good.as_str())
+        .chain(commits.lines())
+        .collect();
+
+    let worktree = Worktree::create(&good)?;
+
+    let good_nanos = worktree
+        .measure(day, &good, &options)?
+        .ok_or_else(|| format!("could not time day {day} at the good revision."))?;
+    let threshold = match limit {
+        Limit::Threshold(threshold) => threshold.as_nanos() as f64,
+        Limit::Ratio(ratio) => good_nanos * ratio,
+    };
+    println!(
+        "Regression threshold: {ANSI_BOLD}{}{ANSI_RESET}",
+        format_nanos(threshold)
+    );
+
+    let bad_nanos = worktree
+        .measure(day, &bad, &options)?
+        .ok_or_else(|| format!("could not time day {day} at the bad revision."))?;
+    if bad_nanos <= threshold {
+        return Err("the bad revision is not slower than the threshold.".into());
+    }
+
+    // invariant: `revs[lo]` is fast, `revs[hi]` is slow.
+    let (mut lo, mut hi) = (0, revs.len() - 1);
+    let (mut lo_nanos, mut hi_nanos) = (good_nanos, bad_nanos);
+    let mut skipped = HashSet::new();
+
+    // try the commit closest to the middle that could be timed so far.
+    while let Some(next) = (lo + 1..hi)
+        .filter(|i| !skipped.contains(i))
+        .min_by_key(|&i| i.abs_diff(lo + (hi - lo) / 2))
+    {
+        match worktree.measure(day, revs[next], &options)? {
+            Some(nanos) if nanos > threshold => (hi, hi_nanos) = (next, nanos),
+            Some(nanos) => (lo, lo_nanos) = (next, nanos),
+            None => {
+                skipped.insert(next);
+            }
+        }
+    }
+
+    println!();
+    if hi - lo > 1 {
+        println!("{ANSI_BOLD}First slow commit is one of:{ANSI_RESET}");
+        for rev in &revs[lo + 1..=hi] {
+            println!("  {}", describe(rev));
+        }
+    } else {
+        println!(
+            "{ANSI_BOLD}First slow commit:{ANSI_RESET} {}",
+            describe(revs[hi])
+        );
+    }
+    println!(
+        "  before: {} ({})",
+        format_nanos(lo_nanos),
+        describe(revs[lo])
+    );
+    println!("  after:  {}", format_nanos(hi_nanos));
+    Ok(())
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("failed to call git: {e}"))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!("`git {}` failed.", args.join(" ")))
+    }
+}
+
+fn rev_parse(rev: &str) -> Result<String, String> {
+    git(&["rev-parse", "--verify", &format!("{rev}^{{commit}}")])
+        .map_err(|_| format!("`{rev}` is not a valid revision."))
+}
+
+fn describe(rev: &str) -> String {
+    git(&["log", "-1", "--format=%h %s", rev]).unwrap_or_else(|_| rev.to_string())
+}
+
+/// The flags of `options` that the runner at `rev` does not know. Runners ignore unknown flags,
+/// so such a revision would be benched with different settings than the others.
+fn unsupported_flags(rev: &str, options: &RunOptions) -> Vec<String> {
+    options
+        .to_args()
+        .into_iter()
+        .filter(|arg| arg.starts_with("--"))
+        .filter(|flag| {
+            let literal = format!("\"{flag}\"");
+            git(&[
+                "grep",
+                "--quiet",
+                "--fixed-strings",
+                &literal,
+                rev,
+                "--",
+                "src",
+            ])
+            .is_err()
+        })
+        .collect()
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn format_nanos(nanos: f64) -> String {
+    format!("{:.1?}", Duration::from_nanos(nanos as u64))
+}
+
+/// A scratch git worktree that revisions are checked out into.
+struct Worktree {
+    path: PathBuf,
+}
+
+impl Worktree {
+    fn create(rev: &str) -> Result<Self, String> {
+        let path = PathBuf::from(WORKTREE_PATH);
+
+        if path.exists() {
+            // a previous run may have been interrupted.
+            let _ = git(&["worktree", "remove", "--force", WORKTREE_PATH]);
+        }
+        let _ = git(&["worktree", "prune"]);
+
+        git(&["worktree", "add", "--detach", "--quiet", WORKTREE_PATH, rev])?;
+        Ok(Self { path })
+    }
+
+    /// Checks out `rev`, then builds and benches the day. Returns the time in nanoseconds, or
+    /// `None` if the day does not build or run at `rev`, or `rev` does not support all options.
+    fn measure(&self, day: Day, rev: &str, options: &RunOptions) -> Result<Option<f64>, String> {
+        print!("{ANSI_ITALIC}{}{ANSI_RESET} ", describe(rev));
+        let _ = stdout().flush();
+
+        let unsupported = unsupported_flags(rev, options);
+        if !unsupported.is_empty() {
+            println!("skipped, does not support {}", unsupported.join(", "));
+            return Ok(None);
+        }
+
+        let worktree = self.path.to_string_lossy();
+        git(&["-C", &worktree, "checkout", "--detach", "--quiet", rev])?;
+
+        // puzzle inputs are not committed, copy them over from the main tree.
+        let input = format!("data/inputs/{day}.txt");
+        fs::copy(&input, self.path.join(&input))
+            .map_err(|e| format!("failed to copy {input}: {e}"))?;
+
+        let nanos = self.run(day, options);
+        match nanos {
+            Some(nanos) => println!("{}", format_nanos(nanos)),
+            None => println!("skipped, could not time day {day}"),
+        }
+        Ok(nanos)
+    }
+
+    fn run(&self, day: Day, options: &RunOptions) -> Option<f64> {
+        let day_padded = day.to_string();
+        if !self.path.join(format!("src/bin/{day_padded}.rs")).exists() {
+            return None;
+        }
+
+        let output = Command::new("cargo")
+            .args(["run", "--quiet", "--release", "--bin", &day_padded, "--"])
+            .args(options.to_args())
+            .current_dir(&self.path)
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+
+        let lines: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect();
+        let timing = child_commands::parse_exec_time(&lines, day);
+
+        match options.part {
+            Some(1) => child_commands::parse_timing_str(timing.part_1.as_deref()?),
+            Some(2) => child_commands::parse_timing_str(timing.part_2.as_deref()?),
+            _ => (timing.part_1.is_some() || timing.part_2.is_some()).then_some(timing.total_nanos),
+        }
+    }
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        let _ = git(&["worktree", "remove", "--force", WORKTREE_PATH]);
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+use std::process;
+
+use crate::template::answers::Answers;
+use crate::template::run_multi::child_commands;
+use crate::template::runner::RunOptions;
+use crate::template::{all_days, readme_stars, Day, ANSI_BOLD, ANSI_RESET};
+
+/// Runs solutions and compares their results against the accepted answers in `data/answers.json`.
+/// Refreshes the stars table in the readme afterwards.
+pub fn handle(day: Option<Day>, is_release: bool) {
+    let answers = Answers::read_from_file();
+
+    let days_to_run: HashSet<Day> =
+        day.map_or_else(|| all_days().collect(), |day| HashSet::from([day]));
+
+    let mut failures = 0;
+    let mut need_space = false;
+
+    for day in all_days().filter(|day| days_to_run.contains(day)) {
+        if need_space {
+            println!();
+        }
+        need_space = true;
+
+        println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
+        println!("------");
+
+        let output = child_commands::run_solution(day, &RunOptions::default(), is_release)
+            .unwrap_or_else(|e| {
+                eprint!("Failed to run day {day}. {e}");
+                process::exit(1);
+            });
+
+        if output.is_empty() {
+            println!("Not solved.");
+            continue;
+        }
+
+        let results = child_commands::parse_answers(&output, day);
+
+        for part in [1, 2] {
+            match (answers.get(day, part), results.part(part)) {
+                (Some(expected), Some(actual)) if expected == actual => {
+                    println!("Part {part}: ✔ matches accepted answer");
+                }
+                (Some(expected), actual) => {
+                    failures += 1;
+                    println!(
+                        "Part {part}: ✖ expected {ANSI_BOLD}{expected}{ANSI_RESET}, got {}",
+                        actual.unwrap_or("nothing")
+                    );
+                }
+                (None, _) => println!("Part {part}: ? no accepted answer recorded"),
+            }
+        }
+    }
+
+    println!();
+    match readme_stars::update(&answers) {
+        Ok(true) => println!("Stored updated stars."),
+        Ok(false) => println!("No accepted answers recorded, leaving the stars untouched."),
+        Err(e) => eprintln!("Failed to store updated stars. {e}"),
+    }
+
+    if failures > 0 {
+        eprintln!("{failures} part(s) did not match their accepted answer.");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,228 @@
+/// Describes the environment a benchmark was recorded in, so timings from different machines or toolchains are not mixed up.
+use std::{collections::HashMap, fmt::Display, fs, process::Command, thread};
+use tinyjson::JsonValue;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fingerprint {
+    pub cpu_model: String,
+    pub cores: usize,
+    pub rustc: String,
+    /// The host triple of `rustc`, which solutions are built for.
+    pub host: String,
+    pub profile: String,
+    pub features: Vec<String>,
+}
+
+impl Fingerprint {
+    /// Collects the fingerprint of the current machine for solutions built with the given profile
+    /// and `cargo` arguments.
+    pub fn current(is_release: bool, cargo_args: &[String]) -> Self {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+        let (cpu_model, cores) = parse_cpuinfo(&cpuinfo);
+
+        let rustc_info = Command::new("rustc")
+            .arg("-vV")
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+            .unwrap_or_default();
+        let (rustc, host) = parse_rustc_info(&rustc_info);
+
+        Self {
+            cpu_model: cpu_model.unwrap_or_else(|| "unknown".into()),
+            cores: cores.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get())),
+            rustc: rustc.unwrap_or_else(|| "unknown".into()),
+            host: host.unwrap_or_else(|| "unknown".into()),
+            profile: if is_release { "release" } else { "dev" }.into(),
+            features: features_from_args(cargo_args),
+        }
+    }
+}
+
+/// The features a `cargo run` invocation enables with `--features`, sorted and deduplicated.
+fn features_from_args(args: &[String]) -> Vec<String> {
+    let mut features = vec![];
+    let mut args = args.iter().take_while(|a| *a != "--");
+    while let Some(arg) = args.next() {
+        let list = match arg.strip_prefix("--features") {
+            Some("") => args.next().map(String::as_str),
+            Some(rest) => rest.strip_prefix('='),
+            None => None,
+        };
+        features.extend(
+            list.unwrap_or_default()
+                .split([',', ' '])
+                .filter(|f| !f.is_empty())
+                .map(String::from),
+        );
+    }
+    features.sort();
+    features.dedup();
+    features
+}
+
+fn parse_cpuinfo(s: &str) -> (Option<String>, Option<usize>) {
+    let model = s
+        .lines()
+        .find(|l| l.starts_with("model name"))
+        .and_then(|l| l.split_once(':'))
+        .map(|(_, v)| v.trim().to_string());
+
+    let cores = s.lines().filter(|l| l.starts_with("processor")).count();
+
+    (model, (cores > 0).then_some(cores))
+}
+
+fn parse_rustc_info(s: &str) -> (Option<String>, Option<String>) {
+    let version = s.lines().next().filter(|l| l.starts_with("rustc"));
+    let host = s.lines().find_map(|l| l.strip_prefix("host: "));
+    (version.map(Into::into), host.map(Into::into))
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let features = if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(", ")
+        };
+        write!(
+            f,
+            "{} ({} cores), {}, {}, {} profile, features: {}",
+            self.cpu_model, self.cores, self.rustc, self.host, self.profile, features
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&Fingerprint> for JsonValue {
+    fn from(value: &Fingerprint) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        map.insert(
+            "cpu_model".into(),
+            JsonValue::String(value.cpu_model.clone()),
+        );
+        #[allow(clippy::cast_precision_loss)]
+        map.insert("cores".into(), JsonValue::Number(value.cores as f64));
+        map.insert("rustc".into(), JsonValue::String(value.rustc.clone()));
+        map.insert("host".into(), JsonValue::String(value.host.clone()));
+        map.insert("profile".into(), JsonValue::String(value.profile.clone()));
+        map.insert(
+            "features".into(),
+            JsonValue::Array(
+                value
+                    .features
+                    .iter()
+                    .cloned()
+                    .map(JsonValue::String)
+                    .collect(),
+            ),
+        );
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for Fingerprint {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected fingerprint to be a JSON object.")?;
+
+        let string = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<String>())
+                .cloned()
+                .ok_or(format!("Expected fingerprint.{key} to be a string."))
+        };
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let cores = json
+            .get("cores")
+            .and_then(|v| v.get::<f64>())
+            .map(|n| *n as usize)
+            .ok_or("Expected fingerprint.cores to be a number.")?;
+
+        let features = json
+            .get("features")
+            .and_then(|v| v.get::<Vec<JsonValue>>())
+            .and_then(|v| {
+                v.iter()
+                    .map(|f| f.get::<String>().cloned())
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or("Expected fingerprint.features to be an array of strings.")?;
+
+        Ok(Fingerprint {
+            cpu_model: string("cpu_model")?,
+            cores,
+            rustc: string("rustc")?,
+            host: string("host")?,
+            profile: string("profile")?,
+            features,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{features_from_args, parse_cpuinfo, parse_rustc_info, Fingerprint};
+    use tinyjson::JsonValue;
+
+    #[test]
+    fn parses_cpuinfo() {
+        let s = "processor\t: 0\nmodel name\t: Some CPU @ 3.00GHz\n\nprocessor\t: 1\nmodel name\t: Some CPU @ 3.00GHz\n";
+        let (model, cores) = parse_cpuinfo(s);
+        assert_eq!(model.as_deref(), Some("Some CPU @ 3.00GHz"));
+        assert_eq!(cores, Some(2));
+    }
+
+    #[test]
+    fn parses_rustc_info() {
+        let s =
+            "rustc 1.80.0 (051478957 2024-07-21)\nbinary: rustc\nhost: x86_64-unknown-linux-gnu\n";
+        let (version, host) = parse_rustc_info(s);
+        assert_eq!(
+            version.as_deref(),
+            Some("rustc 1.80.0 (051478957 2024-07-21)")
+        );
+        assert_eq!(host.as_deref(), Some("x86_64-unknown-linux-gnu"));
+    }
+
+    #[test]
+    fn reads_features_from_cargo_args() {
+        let args = [
+            "run",
+            "--features",
+            "spans",
+            "--bin",
+            "01",
+            "--features=dhat-heap,spans",
+            "--",
+            "--features",
+            "today",
+        ]
+        .map(String::from);
+        assert_eq!(features_from_args(&args), ["dhat-heap", "spans"]);
+        assert!(features_from_args(&["run".into()]).is_empty());
+    }
+
+    #[test]
+    fn roundtrips_json() {
+        let fingerprint = Fingerprint {
+            cpu_model: "Some CPU".into(),
+            cores: 8,
+            rustc: "rustc 1.80.0".into(),
+            host: "aarch64-apple-darwin".into(),
+            profile: "release".into(),
+            features: vec!["dhat-heap".into()],
+        };
+        let json = JsonValue::from(&fingerprint);
+        assert_eq!(Fingerprint::try_from(&json).unwrap(), fingerprint);
+    }
+}
@@ -6,8 +6,11 @@ pub mod runner;
 
 pub use day::*;
 
+mod answers;
 mod day;
+mod fingerprint;
 mod readme_benchmarks;
+mod readme_stars;
 mod run_multi;
 mod timings;
 
@@ -39,6 +42,7 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+/// To pick a part at runtime instead, pass `--part <1|2>` to `solve`, `time` or `all`.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
@@ -61,8 +65,13 @@ macro_rules! solution {
 
         fn main() {
             use $crate::template::runner::*;
+            let options = RunOptions::from_env();
             let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+            $(
+                if options.runs_part($part) {
+                    run_part($func, &input, DAY, $part, &options);
+                }
+            )*
         }
     };
 }
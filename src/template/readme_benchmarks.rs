@@ -10,6 +10,7 @@ static MARKER: &str = "<!--- benchmarking table --->";
 #[derive(Debug)]
 pub enum Error {
     Parser(String),
+    MixedEnvironments(String),
     IO(io::Error),
 }
 
@@ -17,6 +18,7 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Parser(e) => writeln!(f, "Parser error: {e}"),
+            Error::MixedEnvironments(e) => writeln!(f, "Mixed environments: {e}"),
             Error::IO(e) => writeln!(f, "{e}"),
         }
     }
@@ -31,8 +33,8 @@ impl From<std::io::Error> for Error {
 }
 
 pub struct TablePosition {
-    pos_start: usize,
-    pos_end: usize,
+    pub pos_start: usize,
+    pub pos_end: usize,
 }
 
 #[must_use]
@@ -40,8 +42,9 @@ pub fn get_path_for_bin(day: Day) -> String {
     format!("./src/bin/{day}.rs")
 }
 
-fn locate_table(readme: &str) -> Result<TablePosition, Error> {
-    let matches: Vec<_> = readme.match_indices(MARKER).collect();
+/// Locates the region between (and including) the two occurrences of `marker` in the readme.
+pub fn locate_table(readme: &str, marker: &str) -> Result<TablePosition, Error> {
+    let matches: Vec<_> = readme.match_indices(marker).collect();
 
     if matches.len() > 2 {
         return Err(Error::Parser(
@@ -65,6 +68,12 @@ fn locate_table(readme: &str) -> Result<TablePosition, Error> {
 fn construct_table(prefix: &str, timings: Timings, total_millis: f64) -> String {
     let header = format!("{prefix} Benchmarks");
 
+    let environment = if timings.mixed_fingerprints().is_some() {
+        Some("mixed, see `data/timings.json`".to_string())
+    } else {
+        timings.fingerprint().map(ToString::to_string)
+    };
+
     let mut lines: Vec<String> = vec![
         MARKER.into(),
         header,
@@ -86,19 +95,34 @@ fn construct_table(prefix: &str, timings: Timings, total_millis: f64) -> String
 
     lines.push(String::new());
     lines.push(format!("**Total: {total_millis:.2}ms**"));
+
+    if let Some(environment) = environment {
+        lines.push(String::new());
+        lines.push(format!("_Environment: {environment}_"));
+    }
+
     lines.push(MARKER.into());
 
     lines.join("\n")
 }
 
 fn update_content(s: &mut String, timings: Timings, total_millis: f64) -> Result<(), Error> {
-    let positions = locate_table(s)?;
+    let positions = locate_table(s, MARKER)?;
     let table = construct_table("##", timings, total_millis);
     s.replace_range(positions.pos_start..positions.pos_end, &table);
     Ok(())
 }
 
-pub fn update(timings: Timings) -> Result<(), Error> {
+/// Writes the benchmark table to the readme.
+/// Refuses to do so if the timings were recorded in different environments, unless `allow_mixed` is set.
+pub fn update(timings: Timings, allow_mixed: bool) -> Result<(), Error> {
+    if let Some((a, b)) = timings.mixed_fingerprints() {
+        if !allow_mixed {
+            return Err(Error::MixedEnvironments(format!("{a} / {b}")));
+        }
+        eprintln!("Warning: benchmarks were recorded in different environments.");
+    }
+
     let path = "README.md";
     let mut readme = String::from_utf8_lossy(&fs::read(path)?).to_string();
     let total_millis = timings.total_millis();
@@ -120,18 +144,21 @@ mod tests {
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
                     total_nanos: 3e+10,
+                    fingerprint: None,
                 },
                 Timing {
                     day: day!(2),
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
                     total_nanos: 7e+10,
+                    fingerprint: None,
                 },
                 Timing {
                     day: day!(4),
                     part_1: Some("40ms".into()),
                     part_2: Some("50ms".into()),
                     total_nanos: 9e+10,
+                    fingerprint: None,
                 },
             ],
         }
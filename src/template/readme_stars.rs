@@ -0,0 +1,153 @@
+/// Module that updates the readme with the ⭐️ progress table.
+/// Stars are derived from the locally accepted answers in `data/answers.json`.
+use std::{fs, path::Path};
+
+use crate::template::answers::Answers;
+use crate::template::readme_benchmarks::{get_path_for_bin, locate_table, Error};
+use crate::template::{all_days, aoc_cli, Day};
+
+static MARKER: &str = "<!--- advent_readme_stars table --->";
+static STAR: &str = "⭐";
+
+fn star(answers: &Answers, day: Day, part: u8) -> &'static str {
+    if answers.get(day, part).is_some() {
+        STAR
+    } else {
+        ""
+    }
+}
+
+fn construct_table(prefix: &str, year: Option<u16>, answers: &Answers, days: &[Day]) -> String {
+    let header = match year {
+        Some(year) => format!("{prefix} {year} Results"),
+        None => format!("{prefix} Results"),
+    };
+
+    let mut lines: Vec<String> = vec![
+        MARKER.into(),
+        header,
+        String::new(),
+        "| Day | Solution | Part 1 | Part 2 |".into(),
+        "| :---: | :---: | :---: | :---: |".into(),
+    ];
+
+    for &day in days {
+        let puzzle = match year {
+            Some(year) => format!(
+                "[Day {0}](https://adventofcode.com/{year}/day/{0})",
+                day.into_inner()
+            ),
+            None => format!("Day {}", day.into_inner()),
+        };
+        lines.push(format!(
+            "| {} | [{}.rs]({}) | {} | {} |",
+            puzzle,
+            day,
+            get_path_for_bin(day),
+            star(answers, day, 1),
+            star(answers, day, 2)
+        ));
+    }
+
+    lines.push(MARKER.into());
+
+    lines.join("\n")
+}
+
+fn update_content(
+    s: &mut String,
+    year: Option<u16>,
+    answers: &Answers,
+    days: &[Day],
+) -> Result<(), Error> {
+    let positions = locate_table(s, MARKER)?;
+    let table = construct_table("##", year, answers, days);
+    s.replace_range(positions.pos_start..positions.pos_end, &table);
+    Ok(())
+}
+
+/// Regenerates the stars table for every day that has a solution file or an accepted answer.
+///
+/// Without any recorded answers, e.g. in a fresh checkout, the table is left alone instead of
+/// being cleared. Returns whether the table was written.
+pub fn update(answers: &Answers) -> Result<bool, Error> {
+    if answers.data.is_empty() {
+        return Ok(false);
+    }
+    let path = "README.md";
+    let mut readme = String::from_utf8_lossy(&fs::read(path)?).to_string();
+    let days: Vec<Day> = all_days()
+        .filter(|day| {
+            Path::new(&get_path_for_bin(*day)).exists()
+                || answers.data.iter().any(|a| a.day == *day)
+        })
+        .collect();
+    update_content(&mut readme, aoc_cli::get_year(), answers, &days)?;
+    fs::write(path, &readme)?;
+    Ok(true)
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{update_content, MARKER};
+    use crate::{
+        day,
+        template::answers::{Answer, Answers},
+    };
+
+    fn get_mock_answers() -> Answers {
+        Answers {
+            data: vec![
+                Answer {
+                    day: day!(1),
+                    part_1: Some("5".into()),
+                    part_2: Some("4".into()),
+                },
+                Answer {
+                    day: day!(2),
+                    part_1: Some("1985".into()),
+                    part_2: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn errors_if_marker_not_present() {
+        let mut s = "# readme".to_string();
+        update_content(&mut s, Some(2016), &get_mock_answers(), &[day!(1)]).unwrap();
+    }
+
+    #[test]
+    fn updates_existing_stars() {
+        let mut s = format!("foo\n{}{}\nbaz", MARKER, MARKER);
+        let days = [day!(1), day!(2)];
+        update_content(&mut s, Some(2016), &get_mock_answers(), &days).unwrap();
+        update_content(&mut s, Some(2016), &get_mock_answers(), &days).unwrap();
+        assert_eq!(s.matches(MARKER).collect::<Vec<&str>>().len(), 2);
+        assert_eq!(s.matches("## 2016 Results").collect::<Vec<&str>>().len(), 1);
+    }
+
+    #[test]
+    fn format_stars() {
+        let mut s = format!("foo\n{}\n{}\nbaz", MARKER, MARKER);
+        let days = [day!(1), day!(2), day!(3)];
+        update_content(&mut s, Some(2016), &get_mock_answers(), &days).unwrap();
+        let expected = [
+            "foo",
+            "<!--- advent_readme_stars table --->",
+            "## 2016 Results",
+            "",
+            "| Day | Solution | Part 1 | Part 2 |",
+            "| :---: | :---: | :---: | :---: |",
+            "| [Day 1](https://adventofcode.com/2016/day/1) | [01.rs](./src/bin/01.rs) | ⭐ | ⭐ |",
+            "| [Day 2](https://adventofcode.com/2016/day/2) | [02.rs](./src/bin/02.rs) | ⭐ |  |",
+            "| [Day 3](https://adventofcode.com/2016/day/3) | [03.rs](./src/bin/03.rs) |  |  |",
+            "<!--- advent_readme_stars table --->",
+            "baz",
+        ]
+        .join("\n");
+        assert_eq!(s, expected);
+    }
+}
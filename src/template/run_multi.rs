@@ -1,13 +1,20 @@
 use std::{collections::HashSet, io};
 
+use crate::template::runner::RunOptions;
 use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
 
 use super::{
     all_days,
+    fingerprint::Fingerprint,
     timings::{Timing, Timings},
 };
 
-pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -> Option<Timings> {
+pub fn run_multi(
+    days_to_run: &HashSet<Day>,
+    is_release: bool,
+    options: &RunOptions,
+) -> Option<Timings> {
+    let is_timed = options.time;
     let mut timings: Vec<Timing> = Vec::with_capacity(days_to_run.len());
 
     let mut need_space = false;
@@ -24,7 +31,7 @@ pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -
             println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
             println!("------");
 
-            let output = child_commands::run_solution(day, is_timed, is_release).unwrap();
+            let output = child_commands::run_solution(day, options, is_release).unwrap();
 
             if output.is_empty() {
                 println!("Not solved.");
@@ -35,6 +42,15 @@ pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -
         });
 
     if is_timed {
+        // all days are built the same way, so the fingerprint of the first one fits them all.
+        if let Some(first) = timings.first() {
+            let cargo_args = child_commands::cargo_args(first.day, options, is_release);
+            let fingerprint = Fingerprint::current(is_release, &cargo_args);
+            for timing in &mut timings {
+                timing.fingerprint = Some(fingerprint.clone());
+            }
+        }
+
         let timings = Timings { data: timings };
         let total_millis = timings.total_millis();
         println!(
@@ -78,7 +94,9 @@ pub fn get_path_for_bin(day: Day) -> String {
 /// This module encapsulates interaction with these binaries, both invoking them as well as parsing the timing output.
 pub mod child_commands {
     use super::{get_path_for_bin, Error};
-    use crate::template::Day;
+    use crate::template::answers::Answer;
+    use crate::template::runner::RunOptions;
+    use crate::template::{Day, ANSI_BOLD, ANSI_RESET};
     use std::{
         io::{BufRead, BufReader},
         path::Path,
@@ -86,25 +104,54 @@ pub mod child_commands {
         thread,
     };
 
-    /// Run the solution bin for a given day
-    pub fn run_solution(day: Day, is_timed: bool, is_release: bool) -> Result<Vec<String>, Error> {
-        // skip command invocation for days that have not been scaffolded yet.
-        if !Path::new(&get_path_for_bin(day)).exists() {
-            return Ok(vec![]);
-        }
+    /// The features this runner was built with that solutions need as well.
+    pub fn solution_features() -> Vec<&'static str> {
+        [
+            ("dhat-heap", cfg!(feature = "dhat-heap")),
+            ("spans", cfg!(feature = "spans")),
+        ]
+        .into_iter()
+        .filter_map(|(feature, enabled)| enabled.then_some(feature))
+        .collect()
+    }
 
-        let day_padded = day.to_string();
-        let mut args = vec!["run", "--quiet", "--bin", &day_padded];
+    /// The `cargo` arguments that run the solution bin for a given day.
+    pub fn cargo_args(day: Day, options: &RunOptions, is_release: bool) -> Vec<String> {
+        let mut args: Vec<String> = vec!["run".into(), "--quiet".into(), "--bin".into()];
+        args.push(day.to_string());
 
         if is_release {
-            args.push("--release");
+            args.push("--release".into());
+        }
+
+        // build solutions with the features of the runner that change how they run.
+        let features = solution_features();
+        if !features.is_empty() {
+            args.push("--features".into());
+            args.push(features.join(","));
         }
 
-        if is_timed {
-            // mirror `--time` flag to child invocations.
-            args.push("--");
-            args.push("--time");
+        // mirror run options to child invocations.
+        let child_args = options.to_args();
+        if !child_args.is_empty() {
+            args.push("--".into());
+            args.extend(child_args);
         }
+        args
+    }
+
+    /// Run the solution bin for a given day
+    pub fn run_solution(
+        day: Day,
+        options: &RunOptions,
+        is_release: bool,
+    ) -> Result<Vec<String>, Error> {
+        // skip command invocation for days that have not been scaffolded yet.
+        if !Path::new(&get_path_for_bin(day)).exists() {
+            return Ok(vec![]);
+        }
+
+        let args = cargo_args(day, options, is_release);
 
         // spawn child command with piped stdout/stderr.
         // forward output to stdout/stderr while grabbing stdout lines.
@@ -144,6 +191,7 @@ pub mod child_commands {
             part_1: None,
             part_2: None,
             total_nanos: 0_f64,
+            fingerprint: None,
         };
 
         output
@@ -174,29 +222,75 @@ pub mod child_commands {
         timings
     }
 
+    /// Extract the printed result of each part from the output of a solution bin.
+    pub fn parse_answers(output: &[String], day: Day) -> Answer {
+        let mut answer = Answer {
+            day,
+            part_1: None,
+            part_2: None,
+        };
+
+        // intermediate results are overwritten in-place with `\r`, only the last segment is final.
+        let lines: Vec<&str> = output
+            .iter()
+            .map(|l| l.rsplit('\r').next().unwrap_or_default())
+            .collect();
+
+        for (i, line) in lines.iter().enumerate() {
+            let Some((part, rest)) = line.split_once(": ") else {
+                continue;
+            };
+
+            let result = if rest.starts_with('▼') {
+                // multi-line results are printed below the part line.
+                let block: Vec<&str> = lines[i + 1..]
+                    .iter()
+                    .take_while(|l| !l.starts_with("Part "))
+                    .copied()
+                    .collect();
+                Some(block.join("\n").trim_end().to_string())
+            } else {
+                rest.strip_prefix(ANSI_BOLD)
+                    .and_then(|r| r.split_once(ANSI_RESET))
+                    .map(|(r, _)| r.to_string())
+            };
+
+            match part {
+                "Part 1" => answer.part_1 = result,
+                "Part 2" => answer.part_2 = result,
+                _ => {}
+            }
+        }
+
+        answer
+    }
+
     fn parse_to_float(s: &str, postfix: &str) -> Option<f64> {
         s.split(postfix).next()?.parse().ok()
     }
 
     fn parse_time(line: &str) -> Option<(&str, f64)> {
-        // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
         let str_timing = line
             .split(" samples)")
             .next()?
             .split('(')
-            .last()?
+            .next_back()?
             .split('@')
             .next()?
             .trim();
 
-        let parsed_timing = match str_timing {
+        Some((str_timing, parse_timing_str(str_timing)?))
+    }
+
+    /// Parses a formatted duration like `74.13ms` back into nanoseconds.
+    pub fn parse_timing_str(str_timing: &str) -> Option<f64> {
+        // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
+        match str_timing {
             s if s.contains("ns") => s.split("ns").next()?.parse::<f64>().ok(),
             s if s.contains("µs") => parse_to_float(s, "µs").map(|x| x * 1000_f64),
             s if s.contains("ms") => parse_to_float(s, "ms").map(|x| x * 1_000_000_f64),
             s => parse_to_float(s, "s").map(|x| x * 1_000_000_000_f64),
-        }?;
-
-        Some((str_timing, parsed_timing))
+        }
     }
 
     /// copied from: https://github.com/rust-lang/rust/blob/1.64.0/library/std/src/macros.rs#L328-L333
@@ -215,7 +309,7 @@ pub mod child_commands {
 
     #[cfg(feature = "test_lib")]
     mod tests {
-        use super::parse_exec_time;
+        use super::{parse_answers, parse_exec_time};
 
         use crate::day;
 
@@ -263,5 +357,28 @@ pub mod child_commands {
             assert_eq!(res.part_1.is_none(), true);
             assert_eq!(res.part_2.is_none(), true);
         }
+
+        #[test]
+        fn parses_answers() {
+            let res = parse_answers(
+                &[
+                    "Part 1: \x1b[1m42\x1b[0m > benching\rPart 1: \x1b[1m42\x1b[0m (1.0ms @ 10 samples)".into(),
+                    "Part 2: ▼ \rPart 2: ▼  (2.0ms)".into(),
+                    "#..#".into(),
+                    ".##.".into(),
+                    "".into(),
+                ],
+                day!(1),
+            );
+            assert_eq!(res.part_1.unwrap(), "42");
+            assert_eq!(res.part_2.unwrap(), "#..#\n.##.");
+        }
+
+        #[test]
+        fn parses_missing_answers() {
+            let res = parse_answers(&["Part 1: ✖\rPart 1: ✖             ".into()], day!(1));
+            assert_eq!(res.part_1.is_none(), true);
+            assert_eq!(res.part_2.is_none(), true);
+        }
     }
 }
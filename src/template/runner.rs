@@ -2,34 +2,231 @@
 use std::fmt::Display;
 use std::hint::black_box;
 use std::io::{stdout, Write};
+use std::path::PathBuf;
 use std::process::Output;
 use std::time::{Duration, Instant};
-use std::{cmp, env, process};
+use std::{cmp, process};
 
+use crate::error::ParseError;
+use crate::spans;
+use crate::template::answers::Answers;
 use crate::template::ANSI_BOLD;
 use crate::template::{aoc_cli, Day, ANSI_ITALIC, ANSI_RESET};
+use crate::visualize;
 
-pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
+/// Options that the `solve`, `time` and `all` commands forward to a solution binary.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Only run this part of the solution.
+    pub part: Option<u8>,
+    /// Bench each part instead of running it once.
+    pub time: bool,
+    /// Submit the result of this part via aoc-cli.
+    pub submit: Option<u8>,
+    /// Take exactly this many bench samples.
+    pub samples: Option<u128>,
+    /// Bench for roughly this long. Defaults to one second.
+    pub min_time: Option<Duration>,
+    /// Stop benching once this much time has been spent.
+    pub max_time: Option<Duration>,
+    /// Animate the solution's state, if it supports that.
+    pub visualize: bool,
+    /// Frames per second of the animation. Defaults to 10.
+    pub fps: Option<u32>,
+    /// Write the animation's frames to this directory instead of the terminal.
+    pub frames: Option<PathBuf>,
+}
+
+impl RunOptions {
+    /// Reads the run options from command-line arguments.
+    pub fn from_args(args: &mut pico_args::Arguments) -> Result<Self, pico_args::Error> {
+        Ok(Self {
+            part: args.opt_value_from_fn("--part", parse_part)?,
+            time: args.contains("--time"),
+            submit: args.opt_value_from_fn("--submit", parse_part)?,
+            samples: args.opt_value_from_fn("--samples", parse_samples)?,
+            min_time: args.opt_value_from_fn("--min-time", parse_duration)?,
+            max_time: args.opt_value_from_fn("--max-time", parse_duration)?,
+            visualize: args.contains("--visualize"),
+            fps: args.opt_value_from_str("--fps")?,
+            frames: args
+                .opt_value_from_os_str("--frames", |s| Ok::<_, String>(PathBuf::from(s)))?,
+        })
+    }
+
+    /// Reads the run options passed to the current solution binary, exiting on malformed input.
+    pub fn from_env() -> Self {
+        let mut args = pico_args::Arguments::from_env();
+        match Self::from_args(&mut args) {
+            Ok(options) => options,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    /// Serializes the options into arguments for a solution binary.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(part) = self.part {
+            args.extend(["--part".into(), part.to_string()]);
+        }
+        if self.time {
+            args.push("--time".into());
+        }
+        if let Some(part) = self.submit {
+            args.extend(["--submit".into(), part.to_string()]);
+        }
+        if let Some(samples) = self.samples {
+            args.extend(["--samples".into(), samples.to_string()]);
+        }
+        if let Some(min_time) = self.min_time {
+            args.extend(["--min-time".into(), format!("{}ns", min_time.as_nanos())]);
+        }
+        if let Some(max_time) = self.max_time {
+            args.extend(["--max-time".into(), format!("{}ns", max_time.as_nanos())]);
+        }
+        if self.visualize {
+            args.push("--visualize".into());
+        }
+        if let Some(fps) = self.fps {
+            args.extend(["--fps".into(), fps.to_string()]);
+        }
+        if let Some(frames) = &self.frames {
+            args.extend(["--frames".into(), frames.display().to_string()]);
+        }
+        args
+    }
+
+    /// Whether any of the sampling controls were set.
+    pub fn is_sampled(&self) -> bool {
+        self.samples.is_some() || self.min_time.is_some() || self.max_time.is_some()
+    }
+
+    /// Whether the given part should run.
+    pub fn runs_part(&self, part: u8) -> bool {
+        self.part.is_none_or(|p| p == part)
+    }
+}
+
+fn parse_part(s: &str) -> Result<u8, String> {
+    match s.parse() {
+        Ok(part @ (1 | 2)) => Ok(part),
+        _ => Err(format!("expecting part 1 or 2, found `{s}`")),
+    }
+}
+
+fn parse_samples(s: &str) -> Result<u128, String> {
+    match s.parse() {
+        Ok(samples) if samples > 0 => Ok(samples),
+        _ => Err(format!("expecting at least one sample, found `{s}`")),
+    }
+}
+
+/// Parses a duration such as `500ms`, `1.5s` or `20µs`. Plain numbers are read as seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let err = || format!("expecting a duration like `500ms` or `2s`, found `{s}`");
+    let (num, scale) = [
+        ("ns", 1e-9),
+        ("µs", 1e-6),
+        ("us", 1e-6),
+        ("ms", 1e-3),
+        ("s", 1.0),
+    ]
+    .iter()
+    .find_map(|(suffix, scale)| s.strip_suffix(suffix).map(|n| (n, *scale)))
+    .unwrap_or((s, 1.0));
+
+    let secs = num.trim().parse::<f64>().map_err(|_| err())? * scale;
+    Duration::try_from_secs_f64(secs).map_err(|_| err())
+}
+
+/// What a solution part may return: an `Option`, a plain answer, or either behind a
+/// `Result<_, ParseError>` for parts that parse their input.
+pub trait Answer {
+    type Output: Display;
+
+    fn into_answer(self) -> Result<Option<Self::Output>, ParseError>;
+}
+
+impl<T: Display> Answer for Option<T> {
+    type Output = T;
+
+    fn into_answer(self) -> Result<Option<T>, ParseError> {
+        Ok(self)
+    }
+}
+
+impl<A: Answer> Answer for Result<A, ParseError> {
+    type Output = A::Output;
+
+    fn into_answer(self) -> Result<Option<A::Output>, ParseError> {
+        self.and_then(Answer::into_answer)
+    }
+}
+
+macro_rules! plain_answers {
+    ($($t:ty),*) => {
+        $(
+            impl Answer for $t {
+                type Output = $t;
+
+                fn into_answer(self) -> Result<Option<$t>, ParseError> {
+                    Ok(Some(self))
+                }
+            }
+        )*
+    };
+}
+
+plain_answers!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, String);
+
+pub fn run_part<I: Clone, A: Answer>(
+    func: impl Fn(I) -> A,
+    input: I,
+    day: Day,
+    part: u8,
+    options: &RunOptions,
+) {
     let part_str = format!("Part {part}");
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    // discard spans recorded before this part, e.g. while parsing shared input.
+    spans::take();
+
+    if options.visualize {
+        visualize::arm(visualize::Settings {
+            name: format!("day{day}-part{part}"),
+            fps: options.fps.unwrap_or(10),
+            frames: options.frames.clone(),
+        });
+    }
+
+    let func = |input| func(input).into_answer();
+    let (result, duration, samples, spans) = run_timed(func, input, options, |result| {
+        print_result(result, &part_str, "");
+    });
 
     print_result(&result, &part_str, &format_duration(&duration, samples));
 
-    if let Some(result) = result {
-        submit_result(result, day, part);
+    if !spans.is_empty() {
+        print!("{spans}");
+    }
+
+    if let Ok(Some(result)) = result {
+        submit_result(result, day, part, options);
     }
 }
 
-/// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
-///  1. in debug, the function is executed once.
-///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
+/// Run a solution part. The behavior differs depending on whether the solution is benched:
+///  1. by default, the function is executed once.
+///  2. with `--time` or any sampling control, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
 fn run_timed<I: Clone, T>(
     func: impl Fn(I) -> T,
     input: I,
+    options: &RunOptions,
     hook: impl Fn(&T),
-) -> (T, Duration, u128) {
+) -> (T, Duration, u128, spans::Report) {
     let timer = Instant::now();
     let result = {
         let input = input.clone();
@@ -41,27 +238,39 @@ fn run_timed<I: Clone, T>(
     };
     let base_time = timer.elapsed();
 
+    // only report spans of the first run, benching would skew the call counts.
+    let spans = spans::take();
+    visualize::disarm();
+
     hook(&result);
 
-    let run = if std::env::args().any(|x| x == "--time") {
-        bench(func, input, &base_time)
+    let run = if options.time || options.is_sampled() {
+        bench(func, input, &base_time, options)
     } else {
         (base_time, 1)
     };
 
-    (result, run.0, run.1)
+    (result, run.0, run.1, spans)
 }
 
-fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
+fn bench<I: Clone, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    base_time: &Duration,
+    options: &RunOptions,
+) -> (Duration, u128) {
     let mut stdout = stdout();
 
     print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
     let _ = stdout.flush();
 
-    let bench_iterations =
-        (Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000);
+    let bench_iterations = options.samples.unwrap_or_else(|| {
+        let min_time = options.min_time.unwrap_or(Duration::from_secs(1));
+        (min_time.as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000)
+    });
 
     let mut timers: Vec<Duration> = vec![];
+    let bench_timer = Instant::now();
 
     for _ in 0..bench_iterations {
         // need a clone here to make the borrow checker happy.
@@ -69,12 +278,19 @@ fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) ->
         let timer = Instant::now();
         black_box(func(black_box(cloned)));
         timers.push(timer.elapsed());
+
+        if options
+            .max_time
+            .is_some_and(|max_time| bench_timer.elapsed() >= max_time)
+        {
+            break;
+        }
     }
 
     (
         #[allow(clippy::cast_possible_truncation)]
         Duration::from_nanos(average_duration(&timers) as u64),
-        bench_iterations,
+        timers.len() as u128,
     )
 }
 
@@ -94,11 +310,15 @@ fn format_duration(duration: &Duration, samples: u128) -> String {
     }
 }
 
-fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str) {
+fn print_result<T: Display>(
+    result: &Result<Option<T>, ParseError>,
+    part: &str,
+    duration_str: &str,
+) {
     let is_intermediate_result = duration_str.is_empty();
 
     match result {
-        Some(result) => {
+        Ok(Some(result)) => {
             if result.to_string().contains('\n') {
                 let str = format!("{part}: ▼ {duration_str}");
                 if is_intermediate_result {
@@ -118,52 +338,99 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
                 }
             }
         }
-        None => {
+        Ok(None) | Err(_) => {
             if is_intermediate_result {
                 print!("{part}: ✖");
             } else {
                 print!("\r");
                 println!("{part}: ✖             ");
+                if let Err(e) = result {
+                    eprintln!("{e:#}");
+                }
             }
         }
     }
 }
 
-/// Parse the arguments passed to `solve` and try to submit one part of the solution if:
-///  1. we are in `--release` mode.
+/// Try to submit one part of the solution if:
+///  1. `--submit <part>` was passed for this part.
 ///  2. aoc-cli is installed.
+///
+/// Accepted answers are recorded in `data/answers.json`.
 fn submit_result<T: Display>(
     result: T,
     day: Day,
     part: u8,
+    options: &RunOptions,
 ) -> Option<Result<Output, aoc_cli::AocCommandError>> {
-    let args: Vec<String> = env::args().collect();
-
-    if !args.contains(&"--submit".into()) {
+    if options.submit != Some(part) {
         return None;
     }
 
-    if args.len() < 3 {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
+    if aoc_cli::check().is_err() {
+        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
         process::exit(1);
     }
 
-    let part_index = args.iter().position(|x| x == "--submit").unwrap() + 1;
+    println!("Submitting result via aoc-cli...");
+    let result = result.to_string();
+    let output = aoc_cli::submit(day, part, &result);
 
-    let Ok(part_submit) = args[part_index].parse::<u8>() else {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
-        process::exit(1);
-    };
+    if matches!(&output, Ok(output) if aoc_cli::is_accepted(output)) {
+        let mut answers = Answers::read_from_file();
+        answers.record(day, part, &result);
+        if let Err(e) = answers.store_file() {
+            eprintln!("Failed to store accepted answer. {e}");
+        }
+    }
 
-    if part_submit != part {
-        return None;
+    Some(output)
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse_duration, RunOptions};
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("2").unwrap(), Duration::from_secs(2));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("20µs").unwrap(), Duration::from_micros(20));
+        assert_eq!(parse_duration("100ns").unwrap(), Duration::from_nanos(100));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("-1s").is_err());
     }
 
-    if aoc_cli::check().is_err() {
-        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
-        process::exit(1);
+    #[test]
+    fn roundtrips_options() {
+        let options = RunOptions {
+            part: Some(2),
+            time: true,
+            submit: None,
+            samples: Some(50),
+            min_time: Some(Duration::from_millis(100)),
+            max_time: Some(Duration::from_secs(3)),
+            visualize: true,
+            fps: Some(30),
+            frames: Some("data/frames".into()),
+        };
+        let args = options.to_args().into_iter().map(Into::into).collect();
+        let mut args = pico_args::Arguments::from_vec(args);
+        assert_eq!(RunOptions::from_args(&mut args).unwrap(), options);
     }
 
-    println!("Submitting result via aoc-cli...");
-    Some(aoc_cli::submit(day, part, &result.to_string()))
+    #[test]
+    fn rejects_invalid_parts() {
+        let mut args = pico_args::Arguments::from_vec(vec!["--part".into(), "3".into()]);
+        assert!(RunOptions::from_args(&mut args).is_err());
+    }
+
+    #[test]
+    fn rejects_zero_samples() {
+        let mut args = pico_args::Arguments::from_vec(vec!["--samples".into(), "0".into()]);
+        assert!(RunOptions::from_args(&mut args).is_err());
+    }
 }
@@ -1,6 +1,7 @@
 use std::{collections::HashMap, fs, io::Error, str::FromStr};
 use tinyjson::JsonValue;
 
+use crate::template::fingerprint::Fingerprint;
 use crate::template::Day;
 
 static TIMINGS_FILE_PATH: &str = "./data/timings.json";
@@ -12,6 +13,8 @@ pub struct Timing {
     pub part_1: Option<String>,
     pub part_2: Option<String>,
     pub total_nanos: f64,
+    /// The environment this timing was recorded in. `None` for timings stored before fingerprints existed.
+    pub fingerprint: Option<Fingerprint>,
 }
 
 /// Represents benchmark times for a set of days.
@@ -45,7 +48,8 @@ impl Timings {
     }
 
     /// Merge two sets of timings, overwriting `self` with `other` if present.
-    pub fn merge(&self, new: &Self) -> Self {
+    /// Refuses to combine timings recorded in different environments unless `allow_mixed` is set.
+    pub fn merge(&self, new: &Self, allow_mixed: bool) -> Result<Self, String> {
         let mut data: Vec<Timing> = vec![];
 
         for timing in &new.data {
@@ -58,8 +62,34 @@ impl Timings {
             }
         }
 
-        data.sort_unstable_by(|a, b| a.day.cmp(&b.day));
-        Timings { data }
+        data.sort_unstable_by_key(|a| a.day);
+        let merged = Timings { data };
+
+        if !allow_mixed {
+            if let Some((a, b)) = merged.mixed_fingerprints() {
+                return Err(format!(
+                    "refusing to merge timings from different environments:\n  {a}\n  {b}\nPass `--allow-mixed` to merge them anyway."
+                ));
+            }
+        }
+
+        Ok(merged)
+    }
+
+    /// Returns the fingerprint of the first timing that has one.
+    pub fn fingerprint(&self) -> Option<&Fingerprint> {
+        self.data.iter().find_map(|t| t.fingerprint.as_ref())
+    }
+
+    /// Returns two differing fingerprints if the timings were recorded in more than one environment.
+    /// Timings without a fingerprint are not considered.
+    pub fn mixed_fingerprints(&self) -> Option<(&Fingerprint, &Fingerprint)> {
+        let first = self.fingerprint()?;
+        self.data
+            .iter()
+            .filter_map(|t| t.fingerprint.as_ref())
+            .find(|f| *f != first)
+            .map(|other| (first, other))
     }
 
     /// Sum up total duration of timings as millis.
@@ -121,6 +151,10 @@ impl From<&Timing> for JsonValue {
         map.insert("day".into(), JsonValue::String(value.day.to_string()));
         map.insert("total_nanos".into(), JsonValue::Number(value.total_nanos));
 
+        if let Some(fingerprint) = &value.fingerprint {
+            map.insert("fingerprint".into(), JsonValue::from(fingerprint));
+        }
+
         let part_1 = value.part_1.clone().map(JsonValue::String);
         let part_2 = value.part_2.clone().map(JsonValue::String);
 
@@ -173,11 +207,18 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|v| v.get::<f64>().copied())
             .ok_or("Expected timing.total_nanos to be a number.")?;
 
+        let fingerprint = json
+            .get("fingerprint")
+            .filter(|v| !v.is_null())
+            .map(Fingerprint::try_from)
+            .transpose()?;
+
         Ok(Timing {
             day,
             part_1: part_1.cloned(),
             part_2: part_2.cloned(),
             total_nanos,
+            fingerprint,
         })
     }
 }
@@ -198,18 +239,21 @@ mod tests {
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
                     total_nanos: 3e+10,
+                    fingerprint: None,
                 },
                 Timing {
                     day: day!(2),
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
                     total_nanos: 7e+10,
+                    fingerprint: None,
                 },
                 Timing {
                     day: day!(4),
                     part_1: Some("40ms".into()),
                     part_2: None,
                     total_nanos: 4e+10,
+                    fingerprint: None,
                 },
             ],
         }
@@ -289,10 +333,11 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: Some("2ms".into()),
                     total_nanos: 3_000_000_000_f64,
+                    fingerprint: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), true);
+            assert_eq!(timings.is_day_complete(day!(1)), true);
         }
 
         #[test]
@@ -303,10 +348,11 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: None,
                     total_nanos: 1_000_000_000_f64,
+                    fingerprint: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
 
         #[test]
@@ -317,16 +363,18 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0.0,
+                    fingerprint: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
     }
 
     mod merge {
         use crate::{
             day,
+            template::fingerprint::Fingerprint,
             template::timings::{Timing, Timings},
         };
 
@@ -341,9 +389,10 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    fingerprint: None,
                 }],
             };
-            let merged = timings.merge(&other);
+            let merged = timings.merge(&other, false).unwrap();
             assert_eq!(merged.data.len(), 4);
             assert_eq!(merged.data[0].day, day!(1));
             assert_eq!(merged.data[1].day, day!(2));
@@ -361,9 +410,10 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    fingerprint: None,
                 }],
             };
-            let merged = timings.merge(&other);
+            let merged = timings.merge(&other, false).unwrap();
 
             assert_eq!(merged.data.len(), 3);
             assert_eq!(merged.data[0].day, day!(1));
@@ -376,15 +426,42 @@ mod tests {
         fn handles_empty_timings() {
             let timings = Timings::default();
             let other = get_mock_timings();
-            let merged = timings.merge(&other);
+            let merged = timings.merge(&other, false).unwrap();
             assert_eq!(merged.data.len(), 3);
         }
 
+        #[test]
+        fn refuses_mixed_fingerprints() {
+            let fingerprint = |cpu: &str| Fingerprint {
+                cpu_model: cpu.into(),
+                cores: 4,
+                rustc: "rustc 1.80.0".into(),
+                host: "x86_64-unknown-linux-gnu".into(),
+                profile: "release".into(),
+                features: vec![],
+            };
+            let mut timings = get_mock_timings();
+            timings.data[0].fingerprint = Some(fingerprint("laptop"));
+
+            let other = Timings {
+                data: vec![Timing {
+                    day: day!(2),
+                    part_1: None,
+                    part_2: None,
+                    total_nanos: 0_f64,
+                    fingerprint: Some(fingerprint("workstation")),
+                }],
+            };
+
+            assert!(timings.merge(&other, false).is_err());
+            assert!(timings.merge(&other, true).is_ok());
+        }
+
         #[test]
         fn handles_empty_other_timings() {
             let timings = get_mock_timings();
             let other = Timings::default();
-            let merged = timings.merge(&other);
+            let merged = timings.merge(&other, false).unwrap();
             assert_eq!(merged.data.len(), 3);
         }
     }
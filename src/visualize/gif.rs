@@ -0,0 +1,263 @@
+//! A minimal animated GIF encoder.
+//!
+//! Every frame gets its own color table. Frames with more than 256 colors are reduced to a
+//! 6×6×6 color cube.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use super::Rgb;
+
+const MAX_CODES: u16 = 4096;
+
+/// Writes an endlessly looping animation, one frame at a time.
+pub struct GifEncoder<W: Write> {
+    out: W,
+    width: u16,
+    height: u16,
+    /// Delay between frames in hundredths of a second.
+    delay: u16,
+}
+
+impl<W: Write> GifEncoder<W> {
+    /// Writes the header of an animation with the given size and frame delay.
+    pub fn new(mut out: W, width: u16, height: u16, delay: u16) -> io::Result<Self> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // no global color table, background color 0, square pixels.
+        out.write_all(&[0, 0, 0])?;
+        // NETSCAPE2.0 extension, looping forever.
+        out.write_all(&[0x21, 0xff, 11])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[3, 1, 0, 0, 0])?;
+        Ok(Self {
+            out,
+            width,
+            height,
+            delay,
+        })
+    }
+
+    /// Appends a frame of `width * height` pixels in row-major order.
+    pub fn frame(&mut self, pixels: &[Rgb]) -> io::Result<()> {
+        assert_eq!(
+            pixels.len(),
+            usize::from(self.width) * usize::from(self.height)
+        );
+        let (palette, indices) = index_colors(pixels);
+        // the color table holds 2^bits entries, at least two.
+        let bits = (usize::BITS - (palette.len().max(2) - 1).leading_zeros()) as u8;
+
+        // graphic control extension with the frame delay.
+        self.out.write_all(&[0x21, 0xf9, 4, 0])?;
+        self.out.write_all(&self.delay.to_le_bytes())?;
+        self.out.write_all(&[0, 0])?;
+
+        // image descriptor with a local color table.
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&self.width.to_le_bytes())?;
+        self.out.write_all(&self.height.to_le_bytes())?;
+        self.out.write_all(&[0x80 | (bits - 1)])?;
+        for i in 0..1 << bits {
+            self.out.write_all(palette.get(i).unwrap_or(&[0, 0, 0]))?;
+        }
+
+        let min_code_size = bits.max(2);
+        self.out.write_all(&[min_code_size])?;
+        for block in lzw(min_code_size, &indices).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])
+    }
+
+    /// Writes the trailer and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Returns the colors of a frame and every pixel's index into them.
+fn index_colors(pixels: &[Rgb]) -> (Vec<Rgb>, Vec<u8>) {
+    let mut palette = vec![];
+    let mut lookup = HashMap::new();
+    let mut indices = Vec::with_capacity(pixels.len());
+    for &pixel in pixels {
+        let index = *lookup.entry(pixel).or_insert_with(|| {
+            palette.push(pixel);
+            palette.len() - 1
+        });
+        if index > 255 {
+            return color_cube(pixels);
+        }
+        indices.push(index as u8);
+    }
+    (palette, indices)
+}
+
+fn color_cube(pixels: &[Rgb]) -> (Vec<Rgb>, Vec<u8>) {
+    let level = |c: u8| (u16::from(c) * 5 + 127) / 255;
+    let palette = (0..216)
+        .map(|i: u16| [i / 36, i / 6 % 6, i % 6].map(|l| (l * 51) as u8))
+        .collect();
+    let indices = pixels
+        .iter()
+        .map(|&[r, g, b]| (level(r) * 36 + level(g) * 6 + level(b)) as u8)
+        .collect();
+    (palette, indices)
+}
+
+/// Packs codes of varying width into bytes, least significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    len: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= u32::from(code) << self.len;
+        self.len += size;
+        while self.len >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Writes a code, growing the code size once it no longer covers the next free code.
+fn write_code(bits: &mut BitWriter, code: u16, next: u16, size: &mut u8) {
+    bits.write(code, *size);
+    if *size < 12 && next >= 1 << *size {
+        *size += 1;
+    }
+}
+
+/// Compresses color indices with the variable-width LZW flavour used by GIF.
+fn lzw(min_code_size: u8, indices: &[u8]) -> Vec<u8> {
+    let clear = 1 << min_code_size;
+    let end = clear + 1;
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut size = min_code_size + 1;
+    let mut bits = BitWriter::default();
+    bits.write(clear, size);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        bits.write(end, size);
+        return bits.finish();
+    };
+    let mut prefix = u16::from(first);
+    for &index in rest {
+        if let Some(&code) = codes.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        write_code(&mut bits, prefix, next, &mut size);
+        if next < MAX_CODES {
+            codes.insert((prefix, index), next);
+            next += 1;
+        } else {
+            bits.write(clear, size);
+            codes.clear();
+            next = end + 1;
+            size = min_code_size + 1;
+        }
+        prefix = u16::from(index);
+    }
+    write_code(&mut bits, prefix, next, &mut size);
+    bits.write(end, size);
+    bits.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lzw, GifEncoder};
+
+    /// Decodes LZW data the way GIF decoders do.
+    fn unlzw(min_code_size: u8, data: &[u8]) -> Vec<u8> {
+        let clear = 1 << min_code_size;
+        let end = clear + 1;
+        let reset = || -> Vec<Vec<u8>> { (0..=end).map(|i| vec![i as u8]).collect() };
+        let mut table = reset();
+        let mut size = min_code_size + 1;
+        let mut prev: Option<Vec<u8>> = None;
+        let mut out = vec![];
+        let mut pos = 0;
+        loop {
+            let code = (0..size).fold(0, |code, i| {
+                let bit = (data[(pos + i as usize) / 8] >> ((pos + i as usize) % 8)) & 1;
+                code | usize::from(bit) << i
+            });
+            pos += size as usize;
+            if code == clear {
+                table = reset();
+                size = min_code_size + 1;
+                prev = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (table.get(code), &prev) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prev)) => [prev.clone(), vec![prev[0]]].concat(),
+                (None, None) => panic!("invalid code {code}"),
+            };
+            out.extend(&entry);
+            if let Some(prev) = prev {
+                if table.len() < 4096 {
+                    table.push([prev, vec![entry[0]]].concat());
+                }
+            }
+            if table.len() == 1 << size && size < 12 {
+                size += 1;
+            }
+            prev = Some(entry);
+        }
+    }
+
+    #[test]
+    fn roundtrips_lzw() {
+        let inputs = [
+            vec![],
+            vec![1],
+            vec![0, 1, 0, 1, 0, 1, 0, 1, 1, 1, 1],
+            (0..20_000u32)
+                .map(|i| (i * i % 7 + i / 300 % 3) as u8)
+                .collect(),
+            (0..50_000u32)
+                .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+                .collect(),
+        ];
+        for input in inputs {
+            let min_code_size = if input.iter().any(|&i| i > 3) { 8 } else { 2 };
+            assert_eq!(unlzw(min_code_size, &lzw(min_code_size, &input)), input);
+        }
+    }
+
+    #[test]
+    fn writes_frames() {
+        let mut gif = GifEncoder::new(vec![], 2, 1, 10).unwrap();
+        gif.frame(&[[0, 0, 0], [255, 255, 255]]).unwrap();
+        let bytes = gif.finish().unwrap();
+        assert!(bytes.starts_with(b"GIF89a\x02\x00\x01\x00"));
+        assert_eq!(bytes.last(), Some(&0x3b));
+        let table = bytes.windows(6).position(|w| w == [0, 0, 0, 255, 255, 255]);
+        assert!(table.is_some());
+    }
+}
@@ -0,0 +1,355 @@
+//! Animations of a solution's state.
+//!
+//! States implement [`Frame`] and are handed to the [`Visualizer`] returned by [`start`]. That
+//! only happens on `cargo solve <day> --visualize`, otherwise [`start`] returns `None` and the
+//! solution runs as usual. Frames are drawn in place in the terminal at `--fps` frames per
+//! second, or written to the directory given with `--frames` as numbered PPM images and an
+//! animated GIF. Solutions that rather draw a single picture, such as an SVG, hand it to
+//! [`Visualizer::save`].
+//!
+//! ```ignore
+//! let mut visualizer = visualize::start();
+//! for step in steps {
+//!     state.apply(step);
+//!     if let Some(v) = &mut visualizer {
+//!         v.frame(&state);
+//!     }
+//! }
+//! ```
+
+pub mod gif;
+
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::{self, stdout, BufWriter, Write},
+    path::PathBuf,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::grid::{BitGrid, Point};
+use gif::GifEncoder;
+
+/// A color as red, green and blue.
+pub type Rgb = [u8; 3];
+
+pub const BACKGROUND: Rgb = [16, 16, 24];
+pub const FOREGROUND: Rgb = [230, 230, 230];
+
+/// A single picture of an animation.
+pub trait Frame {
+    /// Width and height in pixels.
+    fn size(&self) -> (usize, usize);
+
+    fn pixel(&self, p: Point) -> Rgb;
+
+    /// The frame as terminal text. Defaults to colored half blocks, two pixel rows per line.
+    fn text(&self) -> String {
+        let (width, height) = self.size();
+        let mut text = String::new();
+        for y in (0..height).step_by(2) {
+            for x in 0..width {
+                let [r, g, b] = self.pixel((x, y));
+                let [br, bg, bb] = if y + 1 < height {
+                    self.pixel((x, y + 1))
+                } else {
+                    BACKGROUND
+                };
+                text += &format!("\x1b[38;2;{r};{g};{b}m\x1b[48;2;{br};{bg};{bb}m▀");
+            }
+            text += "\x1b[0m\n";
+        }
+        text
+    }
+}
+
+impl Frame for BitGrid {
+    fn size(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    fn pixel(&self, p: Point) -> Rgb {
+        if self.get(p) {
+            FOREGROUND
+        } else {
+            BACKGROUND
+        }
+    }
+
+    fn text(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Turns any printable state into a frame with one pixel per character.
+///
+/// Spaces and `.` are background, `#` is foreground and every other character gets a color of
+/// its own. The state is printed once, when the frame is created.
+pub struct Text {
+    text: String,
+    lines: Vec<Vec<char>>,
+    width: usize,
+}
+
+impl Text {
+    pub fn new(state: &impl Display) -> Self {
+        let text = state.to_string();
+        let lines: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+        let width = lines.iter().map(Vec::len).max().unwrap_or(0);
+        Self { text, lines, width }
+    }
+}
+
+impl Frame for Text {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.lines.len())
+    }
+
+    fn pixel(&self, (x, y): Point) -> Rgb {
+        const PALETTE: [Rgb; 6] = [
+            [230, 80, 80],
+            [80, 200, 120],
+            [90, 140, 240],
+            [240, 200, 60],
+            [200, 110, 230],
+            [70, 210, 220],
+        ];
+        match self.lines.get(y).and_then(|l| l.get(x)) {
+            None | Some(' ' | '.') => BACKGROUND,
+            Some('#') => FOREGROUND,
+            Some(&c) => PALETTE[c as usize % PALETTE.len()],
+        }
+    }
+
+    fn text(&self) -> String {
+        self.text.clone()
+    }
+}
+
+/// All pixels of a frame, row by row.
+fn pixels(frame: &impl Frame) -> Vec<Rgb> {
+    let (width, height) = frame.size();
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|p| frame.pixel(p))
+        .collect()
+}
+
+/// How the runner asked for a part to be visualized.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    /// Prefix of the written files, e.g. `day08-part1`.
+    pub name: String,
+    pub fps: u32,
+    /// Write frames to this directory instead of the terminal.
+    pub frames: Option<PathBuf>,
+}
+
+static ARMED: Mutex<Option<Settings>> = Mutex::new(None);
+
+/// Visualizes the runs of solution parts started from now on. Called by the runner.
+pub fn arm(settings: Settings) {
+    *ARMED.lock().unwrap() = Some(settings);
+}
+
+/// Stops visualizing, e.g. before benching a part.
+pub fn disarm() {
+    *ARMED.lock().unwrap() = None;
+}
+
+/// Returns a visualizer if the current run should be visualized.
+pub fn start() -> Option<Visualizer> {
+    let settings = ARMED.lock().unwrap().clone()?;
+    Some(Visualizer {
+        delay: Duration::from_secs(1) / settings.fps.max(1),
+        settings,
+        next_frame: Instant::now(),
+        count: 0,
+        gif: None,
+        failed: false,
+    })
+}
+
+/// Draws or records the frames of one run.
+pub struct Visualizer {
+    settings: Settings,
+    delay: Duration,
+    next_frame: Instant,
+    count: usize,
+    gif: Option<Recording>,
+    failed: bool,
+}
+
+/// An animation that keeps the size of its first frame, scaled up.
+struct Recording {
+    encoder: GifEncoder<BufWriter<File>>,
+    width: usize,
+    height: usize,
+    scale: usize,
+}
+
+impl Recording {
+    /// Scales up the `width` by `height` pixels of a frame.
+    fn frame(&mut self, pixels: &[Rgb], width: usize, height: usize) -> io::Result<()> {
+        let scale = self.scale;
+        let scaled: Vec<Rgb> = (0..self.height * scale)
+            .flat_map(|y| (0..self.width * scale).map(move |x| (x / scale, y / scale)))
+            .map(|(x, y)| {
+                if x < width && y < height {
+                    pixels[y * width + x]
+                } else {
+                    BACKGROUND
+                }
+            })
+            .collect();
+        self.encoder.frame(&scaled)
+    }
+}
+
+impl Visualizer {
+    /// Shows the next frame. Failing to write frames is reported once and stops the recording.
+    pub fn frame(&mut self, frame: &impl Frame) {
+        if self.failed {
+            return;
+        }
+        let result = match self.settings.frames.clone() {
+            Some(dir) => self.write_files(frame, dir),
+            None => self.draw(frame),
+        };
+        if let Err(e) = result {
+            eprintln!("Stopped visualizing: {e}");
+            self.failed = true;
+        }
+        self.count += 1;
+    }
+
+    /// Writes a finished picture as `<name>.<extension>` to the `--frames` directory, or the
+    /// current directory without one.
+    pub fn save(&mut self, extension: &str, contents: &str) {
+        let dir = self.settings.frames.clone().unwrap_or_default();
+        let path = dir.join(format!("{}.{extension}", self.settings.name));
+        let result = fs::create_dir_all(&dir).and_then(|()| fs::write(&path, contents));
+        match result {
+            Ok(()) => eprintln!("Saved {}", path.display()),
+            Err(e) => eprintln!("Failed to save {}: {e}", path.display()),
+        }
+    }
+
+    fn draw(&mut self, frame: &impl Frame) -> io::Result<()> {
+        let mut out = stdout().lock();
+        if self.count == 0 {
+            // clear the screen and hide the cursor.
+            write!(out, "\x1b[2J\x1b[?25l")?;
+        }
+        write!(out, "\x1b[H")?;
+        for line in frame.text().lines() {
+            writeln!(out, "{line}\x1b[K")?;
+        }
+        out.flush()?;
+
+        self.next_frame += self.delay;
+        thread::sleep(self.next_frame.saturating_duration_since(Instant::now()));
+        Ok(())
+    }
+
+    fn write_files(&mut self, frame: &impl Frame, dir: PathBuf) -> io::Result<()> {
+        if self.count == 0 {
+            fs::create_dir_all(&dir)?;
+        }
+
+        let (width, height) = frame.size();
+        let pixels = pixels(frame);
+        let name = format!("{}-{:05}.ppm", self.settings.name, self.count);
+        let mut ppm = BufWriter::new(File::create(dir.join(name))?);
+        write!(ppm, "P6\n{width} {height}\n255\n")?;
+        ppm.write_all(pixels.as_flattened())?;
+        ppm.flush()?;
+
+        let recording = match &mut self.gif {
+            Some(recording) => recording,
+            None => {
+                // scale small frames up to roughly 400 pixels.
+                let scale = (400 / width.max(height).max(1)).clamp(1, 16);
+                let size = |n: usize| {
+                    u16::try_from(n * scale)
+                        .map_err(|_| io::Error::other("frames are too large for a GIF"))
+                };
+                let path = dir.join(format!("{}.gif", self.settings.name));
+                let delay = (100 / self.settings.fps.max(1)).max(1) as u16;
+                let encoder = GifEncoder::new(
+                    BufWriter::new(File::create(path)?),
+                    size(width)?,
+                    size(height)?,
+                    delay,
+                )?;
+                self.gif.insert(Recording {
+                    encoder,
+                    width,
+                    height,
+                    scale,
+                })
+            }
+        };
+        recording.frame(&pixels, width, height)
+    }
+}
+
+impl Drop for Visualizer {
+    fn drop(&mut self) {
+        if let Some(recording) = self.gif.take() {
+            if let Err(e) = recording.encoder.finish() {
+                eprintln!("Failed to finish the animation: {e}");
+            }
+        }
+        if self.settings.frames.is_none() && self.count > 0 {
+            print!("\x1b[?25h");
+            let _ = stdout().flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pixels, Frame, Text, BACKGROUND, FOREGROUND};
+    use crate::grid::BitGrid;
+
+    #[test]
+    fn renders_text_as_pixels() {
+        let text = Text::new(&"#.\n a#");
+        assert_eq!(text.size(), (3, 2));
+        assert_eq!(text.pixel((0, 0)), FOREGROUND);
+        assert_eq!(text.pixel((1, 0)), BACKGROUND);
+        assert_eq!(text.pixel((2, 0)), BACKGROUND);
+        assert_ne!(text.pixel((1, 1)), BACKGROUND);
+        assert_eq!(text.text(), "#.\n a#");
+        assert_eq!(pixels(&text)[..3], [FOREGROUND, BACKGROUND, BACKGROUND]);
+        assert_eq!(pixels(&text).len(), 6);
+    }
+
+    #[test]
+    fn renders_half_blocks() {
+        struct Dots;
+
+        impl Frame for Dots {
+            fn size(&self) -> (usize, usize) {
+                (1, 3)
+            }
+
+            fn pixel(&self, (_, y): (usize, usize)) -> [u8; 3] {
+                [y as u8; 3]
+            }
+        }
+
+        assert_eq!(
+            Dots.text(),
+            "\x1b[38;2;0;0;0m\x1b[48;2;1;1;1m▀\x1b[0m\n\
+             \x1b[38;2;2;2;2m\x1b[48;2;16;16;24m▀\x1b[0m\n"
+        );
+
+        let grid = BitGrid::parse_chars("#.\n.#", '#', '.').unwrap();
+        assert_eq!(grid.pixel((1, 1)), FOREGROUND);
+        assert_eq!(grid.text(), "#.\n.#\n");
+    }
+}